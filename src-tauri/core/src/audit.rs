@@ -0,0 +1,186 @@
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::db;
+use crate::journal::Change;
+use crate::{Error, Result};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i32,
+    pub entity: String,
+    pub entity_id: i64,
+    pub action: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub changed_at: String,
+    pub changed_by: String,
+}
+
+/// Maps an audited entity name to the table it lives in.
+pub fn table_for(entity: &str) -> Result<&'static str> {
+    match entity {
+        "employee" => Ok("employees"),
+        "performance" => Ok("performance"),
+        "therapy" => Ok("therapy"),
+        "cost_component" => Ok("cost_components"),
+        _ => Err(Error::Invalid(format!("Unknown entity '{}'", entity))),
+    }
+}
+
+/// Reads the full row of `entity` with the given id as a JSON object,
+/// or `None` if the row does not exist.
+pub fn snapshot(conn: &Connection, entity: &str, id: i64) -> Result<Option<Value>> {
+    let table = table_for(entity)?;
+    let mut stmt = conn.prepare(&format!("SELECT * FROM {} WHERE id = ?1", table))?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+    let row = stmt
+        .query_row(params![id], |row| Ok(Value::Object(row_object(row, &columns)?)))
+        .optional()?;
+    Ok(row)
+}
+
+/// A row as a JSON object of its `columns`.
+pub(crate) fn row_object(row: &Row, columns: &[String]) -> rusqlite::Result<Map<String, Value>> {
+    let mut object = Map::new();
+    for (i, column) in columns.iter().enumerate() {
+        let value = match row.get_ref(i)? {
+            ValueRef::Null => Value::Null,
+            ValueRef::Integer(v) => Value::from(v),
+            ValueRef::Real(v) => Value::from(v),
+            ValueRef::Text(v) => Value::from(String::from_utf8_lossy(v).into_owned()),
+            ValueRef::Blob(v) => Value::from(v.to_vec()),
+        };
+        object.insert(column.clone(), value);
+    }
+    Ok(object)
+}
+
+/// The operating system login, recorded with changes made through a
+/// connection without an actor, see [`set_actor`].
+pub fn current_user() -> String {
+    std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Records `username` as the author of every later change made through
+/// `conn`. Kept in a temporary table, so it ends with the connection.
+pub fn set_actor(conn: &Connection, username: &str) -> Result<()> {
+    conn.execute_batch("CREATE TEMP TABLE IF NOT EXISTS audit_actor (username TEXT NOT NULL)")?;
+    conn.execute("DELETE FROM temp.audit_actor", [])?;
+    conn.execute("INSERT INTO temp.audit_actor (username) VALUES (?1)", params![username])?;
+    Ok(())
+}
+
+fn actor(conn: &Connection) -> Result<String> {
+    let has_actor: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_temp_master WHERE type = 'table' AND name = 'audit_actor'",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_actor == 0 {
+        return Ok(current_user());
+    }
+    let username: Option<String> = conn
+        .query_row("SELECT username FROM temp.audit_actor", [], |row| row.get(0))
+        .optional()?;
+    Ok(username.unwrap_or_else(current_user))
+}
+
+/// Appends one change to the audit log. The table is never updated or
+/// deleted from, except by GDPR erasure and retention, see
+/// [`crate::gdpr::erase`] and [`crate::gdpr::purge_expired`].
+pub fn record(
+    conn: &Connection,
+    entity: &str,
+    entity_id: i64,
+    action: &str,
+    before: Option<&Value>,
+    after: Option<&Value>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO audit_log (entity, entity_id, action, before_json, after_json, changed_at, changed_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'), ?6)",
+        params![
+            entity,
+            entity_id,
+            action,
+            before.map(Value::to_string),
+            after.map(Value::to_string),
+            actor(conn)?
+        ],
+    )?;
+    Ok(())
+}
+
+/// Runs `f` on the existing row of `entity` inside a transaction and records
+/// the row before and after it in the audit log.
+pub fn audited(
+    conn: &Connection,
+    entity: &str,
+    id: i64,
+    action: &str,
+    f: impl FnOnce(&Connection) -> Result<()>,
+) -> Result<Change> {
+    db::transaction(conn, |tx| {
+        let before = snapshot(tx, entity, id)?.ok_or_else(|| Error::NotFound(format!("{} {}", entity, id)))?;
+        f(tx)?;
+        let after = snapshot(tx, entity, id)?;
+        record(tx, entity, id, action, Some(&before), after.as_ref())?;
+        Ok(Change::new(entity, id, Some(before), after))
+    })
+}
+
+/// Like [`audited`] for inserts: `f` returns the id of the new row.
+pub fn audited_insert(
+    conn: &Connection,
+    entity: &str,
+    f: impl FnOnce(&Connection) -> Result<i64>,
+) -> Result<Change> {
+    db::transaction(conn, |tx| {
+        let id = f(tx)?;
+        let after = snapshot(tx, entity, id)?;
+        record(tx, entity, id, "create", None, after.as_ref())?;
+        Ok(Change::new(entity, id, None, after))
+    })
+}
+
+/// Lists audit entries, newest first. `from` and `to` are inclusive
+/// `YYYY-MM-DD` dates; `None` leaves a filter out.
+pub fn query(
+    conn: &Connection,
+    entity: Option<&str>,
+    entity_id: Option<i64>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<AuditEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, entity, entity_id, action, before_json, after_json, changed_at, changed_by
+         FROM audit_log
+         WHERE (?1 IS NULL OR entity = ?1)
+           AND (?2 IS NULL OR entity_id = ?2)
+           AND (?3 IS NULL OR date(changed_at) >= ?3)
+           AND (?4 IS NULL OR date(changed_at) <= ?4)
+         ORDER BY id DESC",
+    )?;
+
+    let parse = |json: Option<String>| json.and_then(|j| serde_json::from_str(&j).ok());
+    let entries = stmt.query_map(params![entity, entity_id, from, to], |row| {
+        Ok(AuditEntry {
+            id: row.get(0)?,
+            entity: row.get(1)?,
+            entity_id: row.get(2)?,
+            action: row.get(3)?,
+            before: parse(row.get(4)?),
+            after: parse(row.get(5)?),
+            changed_at: row.get(6)?,
+            changed_by: row.get(7)?,
+        })
+    })?;
+
+    Ok(entries.collect::<rusqlite::Result<Vec<_>>>()?)
+}
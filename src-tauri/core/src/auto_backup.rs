@@ -0,0 +1,234 @@
+//! Scheduled snapshots of the SQLite file, taken with SQLite's online
+//! backup API while the app keeps running, and their rotation.
+
+use serde::{Serialize, Deserialize};
+use rusqlite::backup::Backup;
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::granularity::{self, Day, Granularity};
+use crate::{db, encryption, settings};
+use crate::{Error, Result};
+
+const PREFIX: &str = "ptrack-";
+const EXTENSION: &str = ".sqlite";
+
+/// Where and how often snapshots are taken and how many are kept.
+/// Stored in the settings table under `auto_backup.*`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutoBackupConfig {
+    /// `None` uses the default folder of the app.
+    pub folder: Option<PathBuf>,
+    pub interval_hours: u64,
+    /// Newest snapshot of each of the last `keep_daily` days is kept.
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+    pub keep_monthly: usize,
+}
+
+impl Default for AutoBackupConfig {
+    fn default() -> Self {
+        AutoBackupConfig {
+            folder: None,
+            interval_hours: 24,
+            keep_daily: 7,
+            keep_weekly: 4,
+            keep_monthly: 12,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub file_name: String,
+    pub path: PathBuf,
+    /// Local time the snapshot was taken, `YYYY-MM-DD HH:MM:SS`.
+    pub taken_at: String,
+    pub size: u64,
+}
+
+impl SnapshotInfo {
+    fn day(&self) -> Day {
+        self.taken_at.get(..10).and_then(|date| granularity::parse_date(date).ok()).unwrap_or(0)
+    }
+}
+
+fn number(conn: &Connection, key: &str, default: u64) -> Result<u64> {
+    Ok(settings::get(conn, key)?.and_then(|v| v.parse().ok()).unwrap_or(default))
+}
+
+pub fn load_config(conn: &Connection) -> Result<AutoBackupConfig> {
+    let default = AutoBackupConfig::default();
+    Ok(AutoBackupConfig {
+        folder: settings::get(conn, "auto_backup.folder")?.filter(|f| !f.is_empty()).map(PathBuf::from),
+        interval_hours: number(conn, "auto_backup.interval_hours", default.interval_hours)?,
+        keep_daily: number(conn, "auto_backup.keep_daily", default.keep_daily as u64)? as usize,
+        keep_weekly: number(conn, "auto_backup.keep_weekly", default.keep_weekly as u64)? as usize,
+        keep_monthly: number(conn, "auto_backup.keep_monthly", default.keep_monthly as u64)? as usize,
+    })
+}
+
+pub fn save_config(conn: &Connection, config: &AutoBackupConfig) -> Result<()> {
+    if config.interval_hours == 0 {
+        return Err(Error::Invalid("The backup interval must be at least one hour".to_string()));
+    }
+    db::transaction(conn, |tx| {
+        let folder = config.folder.as_ref().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+        settings::set(tx, "auto_backup.folder", &folder)?;
+        settings::set(tx, "auto_backup.interval_hours", &config.interval_hours.to_string())?;
+        settings::set(tx, "auto_backup.keep_daily", &config.keep_daily.to_string())?;
+        settings::set(tx, "auto_backup.keep_weekly", &config.keep_weekly.to_string())?;
+        settings::set(tx, "auto_backup.keep_monthly", &config.keep_monthly.to_string())?;
+        Ok(())
+    })
+}
+
+/// Copies the database into a new file in `folder`, named after the
+/// local time. The copy is written under a temporary name first, so a
+/// cut-off snapshot never looks like a finished one. Snapshots of an
+/// encrypted database are encrypted with the same `passphrase`.
+pub fn take_snapshot(conn: &Connection, folder: &Path, passphrase: Option<&str>) -> Result<SnapshotInfo> {
+    std::fs::create_dir_all(folder).map_err(|e| io_error(folder, e))?;
+    let stamp: String = conn.query_row("SELECT strftime('%Y-%m-%d_%H%M%S', 'now', 'localtime')", [], |row| row.get(0))?;
+    let mut file_name = format!("{}{}{}", PREFIX, stamp, EXTENSION);
+    // Two snapshots in the same second, e.g. on exit right after a scheduled one
+    let mut n = 1;
+    while folder.join(&file_name).exists() {
+        n += 1;
+        file_name = format!("{}{}-{}{}", PREFIX, stamp, n, EXTENSION);
+    }
+    let path = folder.join(&file_name);
+    let partial = folder.join(format!("{}.partial", file_name));
+
+    {
+        let mut target = Connection::open(&partial)?;
+        if let Some(passphrase) = passphrase {
+            encryption::unlock(&target, passphrase)?;
+        }
+        let backup = Backup::new(conn, &mut target)?;
+        backup.run_to_completion(100, Duration::from_millis(5), None)?;
+    }
+    std::fs::rename(&partial, &path).map_err(|e| io_error(&path, e))?;
+    snapshot_info(&path).ok_or_else(|| Error::Invalid(format!("Snapshot {} was not written", path.display())))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> Error {
+    Error::Invalid(format!("{}: {}", path.display(), e))
+}
+
+fn snapshot_info(path: &Path) -> Option<SnapshotInfo> {
+    let file_name = path.file_name()?.to_str()?.to_string();
+    let stamp = file_name.strip_prefix(PREFIX)?.strip_suffix(EXTENSION)?;
+    let (date, time) = stamp.split_once('_')?;
+    let time = time.get(..6)?;
+    if date.len() != 10 || !time.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(SnapshotInfo {
+        taken_at: format!("{} {}:{}:{}", date, &time[0..2], &time[2..4], &time[4..6]),
+        size: std::fs::metadata(path).ok()?.len(),
+        path: path.to_path_buf(),
+        file_name,
+    })
+}
+
+/// Snapshots in `folder`, newest first. A missing folder has none.
+pub fn list(folder: &Path) -> Result<Vec<SnapshotInfo>> {
+    let entries = match std::fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error(folder, e)),
+    };
+    let mut snapshots: Vec<SnapshotInfo> = entries.filter_map(|e| snapshot_info(&e.ok()?.path())).collect();
+    // `-2` suffixes of snapshots taken in the same second sort after the first one
+    snapshots.sort_by(|a, b| (&b.taken_at, b.file_name.len(), &b.file_name).cmp(&(&a.taken_at, a.file_name.len(), &a.file_name)));
+    Ok(snapshots)
+}
+
+/// Indexes into `snapshots` (newest first) that the rotation keeps: the
+/// newest one overall and the newest of each of the most recent days,
+/// weeks and months up to the configured counts.
+fn kept(snapshots: &[SnapshotInfo], config: &AutoBackupConfig) -> HashSet<usize> {
+    let mut keep = HashSet::new();
+    if !snapshots.is_empty() {
+        keep.insert(0);
+    }
+    let periods = [
+        (config.keep_daily, Granularity::Day),
+        (config.keep_weekly, Granularity::Week),
+        (config.keep_monthly, Granularity::Month),
+    ];
+    for (count, period) in periods {
+        let mut seen = HashSet::new();
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            if seen.len() >= count {
+                break;
+            }
+            if seen.insert(period.span(snapshot.day()).0) {
+                keep.insert(i);
+            }
+        }
+    }
+    keep
+}
+
+/// Deletes the snapshots the rotation no longer keeps and returns them.
+pub fn rotate(folder: &Path, config: &AutoBackupConfig) -> Result<Vec<SnapshotInfo>> {
+    let snapshots = list(folder)?;
+    let keep = kept(&snapshots, config);
+    let mut removed = Vec::new();
+    for (i, snapshot) in snapshots.into_iter().enumerate() {
+        if !keep.contains(&i) {
+            std::fs::remove_file(&snapshot.path).map_err(|e| io_error(&snapshot.path, e))?;
+            removed.push(snapshot);
+        }
+    }
+    Ok(removed)
+}
+
+/// Whether the newest snapshot in `folder` is older than the interval.
+pub fn is_due(folder: &Path, config: &AutoBackupConfig) -> Result<bool> {
+    let Some(newest) = list(folder)?.into_iter().next() else {
+        return Ok(true);
+    };
+    let age = std::fs::metadata(&newest.path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .unwrap_or(Duration::MAX);
+    Ok(age >= Duration::from_secs(config.interval_hours * 3600))
+}
+
+/// Opens the snapshot at `path` for [`restore_from`] and checks that it
+/// can be read. Encrypted snapshots need the passphrase they were taken
+/// with.
+pub fn open(path: &Path, passphrase: Option<&str>) -> Result<Connection> {
+    if snapshot_info(path).is_none() {
+        return Err(Error::Invalid(format!("{} is not a snapshot", path.display())));
+    }
+    let source = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    if let Some(passphrase) = passphrase {
+        encryption::unlock(&source, passphrase)?;
+    }
+    source.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))?;
+    Ok(source)
+}
+
+/// Replaces the database behind `conn` with the opened snapshot `source`
+/// and brings it to the current schema.
+pub fn restore_from(conn: &mut Connection, source: &Connection) -> Result<()> {
+    {
+        let backup = Backup::new(source, conn)?;
+        backup.run_to_completion(100, Duration::from_millis(5), None)?;
+    }
+    db::init(conn)
+}
+
+/// Replaces the database behind `conn` with the snapshot at `path`. Take
+/// a snapshot first if the current state may still be needed.
+pub fn restore(conn: &mut Connection, path: &Path, passphrase: Option<&str>) -> Result<()> {
+    let source = open(path, passphrase)?;
+    restore_from(conn, &source)
+}
@@ -0,0 +1,346 @@
+//! Versioned JSON archives of a whole practice. They hold every table of
+//! the practice, including the recycle bin and closed periods, and can be
+//! restored into databases of a newer schema.
+
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Map, Value};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::journal::to_sql;
+use crate::{audit, db, migrations, period};
+use crate::{Error, Result};
+
+/// Marks a JSON file as a backup of this app.
+pub const BACKUP_FORMAT: &str = "performance-tracker-backup";
+/// Version of the archive layout, raised whenever its shape changes.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Tables in a backup, in the order they are restored. The audit log
+/// stays with the database it was written in.
+pub const TABLES: &[&str] = &[
+    "employees",
+    "users",
+    "cost_components",
+    "therapy",
+    "performance",
+    "settings",
+    "import_profiles",
+    "closed_periods",
+    "closed_period_rates",
+    "closed_period_prices",
+    "period_log",
+];
+
+type Rows = Vec<Map<String, Value>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backup {
+    pub format: String,
+    pub format_version: u32,
+    /// Schema version of the database the backup was taken from.
+    pub schema_version: u32,
+    pub created_at: String,
+    pub tables: BTreeMap<String, Rows>,
+    /// Hex SHA-256 of each table's rows as compact JSON.
+    pub checksums: BTreeMap<String, String>,
+}
+
+/// What a restore does with rows whose id already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictMode {
+    /// Empty the tables first, so the database matches the backup.
+    Replace,
+    /// Keep existing rows and only add the missing ones.
+    Merge,
+    /// Restore nothing if any row already exists.
+    Abort,
+}
+
+/// Rows written and rows kept by a merge, by table.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    pub restored: BTreeMap<String, usize>,
+    pub skipped: BTreeMap<String, usize>,
+    /// Employees of the backup whose id belongs to someone else here, with
+    /// the id they and their records were merged under.
+    pub remapped_employees: BTreeMap<i64, i64>,
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![table],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn primary_key(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1) WHERE pk > 0 ORDER BY pk")?;
+    let columns = stmt.query_map(params![table], |row| row.get(0))?;
+    Ok(columns.collect::<rusqlite::Result<_>>()?)
+}
+
+fn dump(conn: &Connection, with_users: bool) -> Result<BTreeMap<String, Rows>> {
+    let mut tables = BTreeMap::new();
+    for table in TABLES {
+        if (*table == "users" && !with_users) || !table_exists(conn, table)? {
+            continue;
+        }
+        let mut stmt = conn.prepare(&format!("SELECT * FROM {} ORDER BY rowid", table))?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let rows = stmt.query_map([], |row| audit::row_object(row, &columns))?;
+        tables.insert(table.to_string(), rows.collect::<rusqlite::Result<_>>()?);
+    }
+    Ok(tables)
+}
+
+fn checksum(rows: &Rows) -> String {
+    let json = serde_json::to_vec(rows).expect("JSON rows always serialize");
+    Sha256::digest(json).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn insert(conn: &Connection, table: &str, row: &Map<String, Value>) -> Result<()> {
+    let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    conn.execute(
+        &format!("INSERT INTO {} ({}) VALUES ({})", table, columns.join(", "), placeholders.join(", ")),
+        params_from_iter(row.values().map(to_sql)),
+    )?;
+    Ok(())
+}
+
+fn exists(conn: &Connection, table: &str, key: &[String], row: &Map<String, Value>) -> Result<bool> {
+    let condition: Vec<String> = key.iter().enumerate().map(|(i, column)| format!("{} = ?{}", column, i + 1)).collect();
+    let values = key.iter().map(|column| to_sql(row.get(column).unwrap_or(&Value::Null)));
+    let count: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM {} WHERE {}", table, condition.join(" AND ")),
+        params_from_iter(values),
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Columns besides the primary key that identify a row, so a merge doesn't
+/// add the same therapy, login or day twice.
+fn natural_key(table: &str) -> Vec<String> {
+    let columns: &[&str] = match table {
+        "therapy" => &["therapy_name"],
+        "users" => &["username"],
+        "performance" => &["employee_id", "date"],
+        _ => &[],
+    };
+    columns.iter().map(|c| c.to_string()).collect()
+}
+
+/// Whether the employee with the id of backup `row` is the same person
+/// here. Either the name or the join date may have changed since.
+fn same_employee(conn: &Connection, row: &Map<String, Value>) -> Result<bool> {
+    let value = |column| to_sql(row.get(column).unwrap_or(&Value::Null));
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM employees WHERE id = ?1 AND (name = ?2 OR join_date = ?3)",
+        params![value("id"), value("name"), value("join_date")],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// The backup id and the local id of an employee with the name and join
+/// date of backup `row` under a different id.
+fn employee_elsewhere(conn: &Connection, row: &Map<String, Value>) -> Result<Option<(i64, i64)>> {
+    let value = |column| to_sql(row.get(column).unwrap_or(&Value::Null));
+    let id = conn
+        .query_row(
+            "SELECT id FROM employees WHERE id <> ?1 AND name = ?2 AND join_date = ?3",
+            params![value("id"), value("name"), value("join_date")],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(id.and_then(|id| Some((row.get("id")?.as_i64()?, id))))
+}
+
+/// Takes a backup of every table of the practice. The backup is plain
+/// JSON, even of an encrypted database, so user accounts and their
+/// password hashes are only in it `with_users`. Salaries always are.
+pub fn create(conn: &Connection, with_users: bool) -> Result<Backup> {
+    let tables = dump(conn, with_users)?;
+    Ok(Backup {
+        format: BACKUP_FORMAT.to_string(),
+        format_version: BACKUP_VERSION,
+        schema_version: migrations::schema_version(conn)?,
+        created_at: conn.query_row("SELECT datetime('now')", [], |row| row.get(0))?,
+        checksums: tables.iter().map(|(table, rows)| (table.clone(), checksum(rows))).collect(),
+        tables,
+    })
+}
+
+/// Checks that `backup` is a backup this app can read and that no table
+/// was changed or cut off since it was written.
+pub fn verify(backup: &Backup) -> Result<()> {
+    if backup.format != BACKUP_FORMAT {
+        return Err(Error::Invalid("The file is not a backup of this app".to_string()));
+    }
+    if backup.format_version > BACKUP_VERSION {
+        return Err(Error::Invalid(format!(
+            "Backup format {} is newer than this app supports ({})",
+            backup.format_version, BACKUP_VERSION
+        )));
+    }
+    for (table, rows) in &backup.tables {
+        if !TABLES.contains(&table.as_str()) {
+            return Err(Error::Invalid(format!("Unknown table '{}' in backup", table)));
+        }
+        if backup.checksums.get(table) != Some(&checksum(rows)) {
+            return Err(Error::Invalid(format!("Checksum mismatch in table '{}'. The backup is damaged.", table)));
+        }
+    }
+    Ok(())
+}
+
+/// The rows of `backup` as they look in the current schema. Backups of an
+/// older schema are loaded into a scratch database at their version and
+/// migrated from there.
+fn upgraded_tables(backup: &Backup) -> Result<BTreeMap<String, Rows>> {
+    let latest = migrations::latest_version();
+    if backup.schema_version > latest {
+        return Err(Error::Invalid(format!(
+            "Backup schema version {} is newer than this app supports ({})",
+            backup.schema_version, latest
+        )));
+    }
+    if backup.schema_version == latest {
+        return Ok(backup.tables.clone());
+    }
+
+    let scratch = Connection::open_in_memory()?;
+    migrations::migrate_to(&scratch, backup.schema_version)?;
+    for table in TABLES {
+        for row in backup.tables.get(*table).into_iter().flatten() {
+            insert(&scratch, table, row)?;
+        }
+    }
+    migrations::migrate(&scratch)?;
+    dump(&scratch, backup.tables.contains_key("users"))
+}
+
+/// Restores `backup` in one transaction. Rows are matched by their
+/// primary key and by the columns that identify them, like employee and
+/// date of a day; see [`ConflictMode`] for what happens to existing ones.
+/// Merges and aborting restores don't add performance to closed months.
+///
+/// When merging, a backup employee whose id belongs to a different person
+/// here is added under a new id, and their records follow them. Other rows
+/// whose id is taken by a different row get a new id as well.
+///
+/// Backups without user accounts keep the accounts here. Those linked to
+/// an employee the restore removed are unlinked.
+pub fn restore(conn: &Connection, backup: &Backup, mode: ConflictMode) -> Result<RestoreSummary> {
+    verify(backup)?;
+    let tables = upgraded_tables(backup)?;
+
+    db::transaction(conn, |tx| {
+        let mut summary = RestoreSummary::default();
+        let mut added_employees = HashSet::new();
+        if mode == ConflictMode::Replace {
+            // Accounts kept here may point at employees until they are unlinked below
+            tx.pragma_update(None, "defer_foreign_keys", true)?;
+            for table in TABLES.iter().rev().filter(|t| tables.contains_key(**t)) {
+                tx.execute(&format!("DELETE FROM {}", table), [])?;
+            }
+        }
+
+        for table in TABLES {
+            let Some(rows) = tables.get(*table) else { continue };
+            let key = primary_key(tx, table)?;
+            let natural = natural_key(table);
+            let renumberable = key == ["id"];
+            // New ids come after every id here and in the backup, so they
+            // never take the id of a row restored later
+            let mut next_id = if renumberable {
+                let local: Option<i64> = tx.query_row(&format!("SELECT MAX(id) FROM {}", table), [], |row| row.get(0))?;
+                let backup = rows.iter().filter_map(|row| row.get("id")?.as_i64()).max();
+                local.max(backup).unwrap_or(0) + 1
+            } else {
+                0
+            };
+            let (mut restored, mut skipped) = (0, 0);
+            for row in rows {
+                let mut row = row.clone();
+                // Records of an employee added under a new id are new here
+                let employee = row.get("employee_id").and_then(Value::as_i64);
+                let mut new_id = match employee.and_then(|id| summary.remapped_employees.get(&id)) {
+                    Some(id) => {
+                        row.insert("employee_id".to_string(), (*id).into());
+                        renumberable && added_employees.contains(id)
+                    }
+                    None => false,
+                };
+
+                let taken = !new_id && exists(tx, table, &key, &row)?;
+                let duplicate = !natural.is_empty() && exists(tx, table, &natural, &row)?;
+                if mode == ConflictMode::Abort && (taken || duplicate) {
+                    let columns = if taken { &key } else { &natural };
+                    let id: Vec<String> = columns.iter().map(|c| row.get(c).map_or("null".to_string(), Value::to_string)).collect();
+                    return Err(Error::Invalid(format!(
+                        "Restore aborted: {} {} already exists",
+                        table,
+                        id.join("/")
+                    )));
+                }
+                if duplicate {
+                    skipped += 1;
+                    continue;
+                }
+                if taken {
+                    let different = match *table {
+                        "employees" => !same_employee(tx, &row)?,
+                        _ => renumberable && !natural.is_empty(),
+                    };
+                    if !different {
+                        skipped += 1;
+                        continue;
+                    }
+                    // Merged before under another id
+                    if *table == "employees" {
+                        if let Some((old_id, id)) = employee_elsewhere(tx, &row)? {
+                            summary.remapped_employees.insert(old_id, id);
+                            skipped += 1;
+                            continue;
+                        }
+                    }
+                    new_id = true;
+                }
+
+                if *table == "performance" && mode != ConflictMode::Replace {
+                    if let Some(date) = row.get("date").and_then(Value::as_str) {
+                        period::ensure_open(tx, date)?;
+                    }
+                }
+                if new_id {
+                    let old_id = row.insert("id".to_string(), next_id.into()).and_then(|id| id.as_i64());
+                    if let (true, Some(old_id)) = (*table == "employees", old_id) {
+                        summary.remapped_employees.insert(old_id, next_id);
+                        added_employees.insert(next_id);
+                    }
+                    next_id += 1;
+                }
+                insert(tx, table, &row)?;
+                restored += 1;
+            }
+            summary.restored.insert(table.to_string(), restored);
+            if skipped > 0 {
+                summary.skipped.insert(table.to_string(), skipped);
+            }
+        }
+        if !tables.contains_key("users") {
+            tx.execute("UPDATE users SET employee_id = NULL WHERE employee_id NOT IN (SELECT id FROM employees)", [])?;
+        }
+
+        let details = json!({ "mode": mode, "created_at": backup.created_at, "restored": summary.restored });
+        audit::record(tx, "backup", 0, "restore", None, Some(&details))?;
+        Ok(summary)
+    })
+}
@@ -0,0 +1,305 @@
+//! Command-line companion of the performance tracker for reports and
+//! maintenance scripts. Works on the same SQLite database as the app.
+
+use performance_tracker_core::stats::{self, MonthlyStats};
+use performance_tracker_core::backup::{self, Backup, ConflictMode};
+use performance_tracker_core::granularity::Granularity;
+use performance_tracker_core::{db, employee, encryption, migrations, transfer};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: ptrack --db <path> <command> [options]
+
+Commands:
+  employees [--format table|csv|json]
+      List employees.
+  stats [--employee <id>] [--from YYYY-MM --to YYYY-MM] [--by day|week|month|quarter|year]
+        [--format table|csv|json]
+      Stats of one employee, or of the whole practice without --employee, per
+      month unless --by says otherwise.
+  export <file.json> [--with-users]
+      Write a JSON backup of the whole practice. The file is not encrypted,
+      even for an encrypted database, and holds every salary; keep it safe.
+      User accounts and their password hashes are only written with
+      --with-users.
+  import <file.json> [--on-conflict replace|merge|abort]
+      Restore a file written by `export`. Existing rows are kept with merge
+      (the default), replaced with replace, or stop the restore with abort.
+  backup <file.sqlite>
+      Write a consistent copy of the database to a new file.
+  migrate
+      Apply pending schema migrations.
+  encrypt
+      Encrypt the plain database in place with $PTRACK_PASSPHRASE.
+
+Encrypted databases are opened with the passphrase in $PTRACK_PASSPHRASE.";
+
+const PASSPHRASE_VAR: &str = "PTRACK_PASSPHRASE";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+struct Args {
+    db: PathBuf,
+    command: String,
+    positional: Vec<String>,
+    employee: Option<i32>,
+    from: Option<String>,
+    to: Option<String>,
+    by: Granularity,
+    format: Format,
+    on_conflict: ConflictMode,
+    with_users: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut db = None;
+    let mut command = None;
+    let mut positional = Vec::new();
+    let mut employee = None;
+    let mut from = None;
+    let mut to = None;
+    let mut by = Granularity::Month;
+    let mut format = Format::Table;
+    let mut on_conflict = ConflictMode::Merge;
+    let mut with_users = false;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "--db" => db = Some(PathBuf::from(value("--db")?)),
+            "--employee" => {
+                let id = value("--employee")?;
+                employee = Some(id.parse().map_err(|_| format!("Invalid employee id '{}'", id))?);
+            }
+            "--from" => from = Some(value("--from")?),
+            "--to" => to = Some(value("--to")?),
+            "--by" => {
+                by = match value("--by")?.as_str() {
+                    "day" => Granularity::Day,
+                    "week" => Granularity::Week,
+                    "month" => Granularity::Month,
+                    "quarter" => Granularity::Quarter,
+                    "year" => Granularity::Year,
+                    other => return Err(format!("Unknown granularity '{}'", other)),
+                }
+            }
+            "--format" => {
+                format = match value("--format")?.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    other => return Err(format!("Unknown format '{}'", other)),
+                }
+            }
+            "--on-conflict" => {
+                on_conflict = match value("--on-conflict")?.as_str() {
+                    "replace" => ConflictMode::Replace,
+                    "merge" => ConflictMode::Merge,
+                    "abort" => ConflictMode::Abort,
+                    other => return Err(format!("Unknown conflict mode '{}'", other)),
+                }
+            }
+            "--with-users" => with_users = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option '{}'", flag)),
+            _ if command.is_none() => command = Some(arg),
+            _ => positional.push(arg),
+        }
+    }
+
+    Ok(Args {
+        db: db.ok_or("--db <path> is required")?,
+        command: command.ok_or("No command given")?,
+        positional,
+        employee,
+        from,
+        to,
+        by,
+        format,
+        on_conflict,
+        with_users,
+    })
+}
+
+/// Prints rows as an aligned table or as CSV.
+fn print_rows(format: Format, headers: &[&str], rows: &[Vec<String>]) {
+    if format == Format::Csv {
+        let quote = |field: &str| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        };
+        println!("{}", headers.join(","));
+        for row in rows {
+            println!("{}", row.iter().map(|f| quote(f)).collect::<Vec<_>>().join(","));
+        }
+        return;
+    }
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+    let line = |fields: Vec<&str>| {
+        let padded: Vec<String> = fields.iter().zip(&widths).map(|(f, w)| format!("{:<w$}", f, w = w)).collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+    line(headers.to_vec());
+    line(widths.iter().map(|w| &"--------------------------------"[..(*w).min(32)]).collect());
+    for row in rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
+
+fn print_json(value: &impl Serialize) -> Result<(), String> {
+    println!("{}", serde_json::to_string_pretty(value).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+fn list_employees(conn: &Connection, format: Format) -> Result<(), String> {
+    let employees = employee::list(conn).map_err(|e| e.to_string())?;
+    if format == Format::Json {
+        return print_json(&employees);
+    }
+    let rows: Vec<Vec<String>> = employees
+        .iter()
+        .map(|e| {
+            vec![
+                e.id.to_string(),
+                e.name.clone(),
+                e.join_date.clone(),
+                format!("{:.2}", e.monthly_rate),
+                e.avg_hours.to_string(),
+            ]
+        })
+        .collect();
+    print_rows(format, &["id", "name", "join_date", "monthly_rate", "avg_hours"], &rows);
+    Ok(())
+}
+
+fn print_stats(conn: &Connection, args: &Args) -> Result<(), String> {
+    let (from, to) = (args.from.as_deref(), args.to.as_deref());
+    if from.is_some() != to.is_some() {
+        return Err("--from and --to must be given together".to_string());
+    }
+    let (from, to) = stats::month_range(from, to).map_err(|e| e.to_string())?;
+    let (from, to) = (from.as_deref(), to.as_deref());
+    let stats: Vec<MonthlyStats> = match args.employee {
+        Some(id) => stats::stats_by(conn, id, args.by, from, to, false),
+        None => stats::practice_stats_by(conn, args.by, from, to, false),
+    }
+    .map_err(|e| e.to_string())?;
+
+    if args.format == Format::Json {
+        return print_json(&stats);
+    }
+    let rows: Vec<Vec<String>> = stats
+        .iter()
+        .map(|s| {
+            vec![
+                s.month.clone(),
+                format!("{:.2}", s.total_hours),
+                format!("{:.2}", s.hours_needed),
+                s.work_days.to_string(),
+                s.sick_days.to_string(),
+                s.vacation_days.to_string(),
+                format!("{:.2}", s.generated_income),
+                format!("{:.2}", s.cost),
+                format!("{:.2}", s.generated_income - s.cost),
+            ]
+        })
+        .collect();
+    let period = if args.by == Granularity::Month { "month" } else { "period" };
+    print_rows(
+        args.format,
+        &[period, "hours", "hours_needed", "work_days", "sick_days", "vacation_days", "income", "cost", "margin"],
+        &rows,
+    );
+    Ok(())
+}
+
+fn file_argument(args: &Args) -> Result<PathBuf, String> {
+    match args.positional.as_slice() {
+        [file] => Ok(PathBuf::from(file)),
+        _ => Err(format!("{} needs exactly one file argument", args.command)),
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let passphrase = std::env::var(PASSPHRASE_VAR).ok().filter(|p| !p.is_empty());
+    if args.command == "encrypt" {
+        let passphrase = passphrase.ok_or_else(|| format!("Set {} to the new passphrase", PASSPHRASE_VAR))?;
+        encryption::encrypt_in_place(&args.db, &passphrase).map_err(|e| e.to_string())?;
+        eprintln!("Encrypted {}", args.db.display());
+        return Ok(());
+    }
+    if args.command == "migrate" {
+        let conn = Connection::open(&args.db).map_err(|e| e.to_string())?;
+        if let Some(passphrase) = &passphrase {
+            encryption::unlock(&conn, passphrase).map_err(|e| e.to_string())?;
+        }
+        let before = migrations::schema_version(&conn).map_err(|e| e.to_string())?;
+        let after = migrations::migrate(&conn).map_err(|e| e.to_string())?;
+        println!("Schema version {} -> {}", before, after);
+        return Ok(());
+    }
+
+    let conn = match &passphrase {
+        Some(passphrase) => db::open_encrypted(&args.db, passphrase),
+        None => db::open(&args.db),
+    }
+    .map_err(|e| e.to_string())?;
+    match args.command.as_str() {
+        "employees" => list_employees(&conn, args.format),
+        "stats" => print_stats(&conn, &args),
+        "export" => {
+            let path = file_argument(&args)?;
+            let backup = backup::create(&conn, args.with_users).map_err(|e| e.to_string())?;
+            let json = serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())?;
+            std::fs::write(&path, json).map_err(|e| e.to_string())?;
+            let rows: usize = backup.tables.values().map(Vec::len).sum();
+            eprintln!("Exported {} rows of {} tables to {}", rows, backup.tables.len(), path.display());
+            Ok(())
+        }
+        "import" => {
+            let path = file_argument(&args)?;
+            let json = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            let backup: Backup = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            let summary = backup::restore(&conn, &backup, args.on_conflict).map_err(|e| e.to_string())?;
+            for (table, restored) in &summary.restored {
+                let skipped = summary.skipped.get(table).copied().unwrap_or(0);
+                eprintln!("{}: {} restored, {} kept", table, restored, skipped);
+            }
+            Ok(())
+        }
+        "backup" => {
+            let path = file_argument(&args)?;
+            transfer::backup_to(&conn, &path).map_err(|e| e.to_string())?;
+            eprintln!("Backup written to {}", path.display());
+            Ok(())
+        }
+        other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+    }
+}
+
+fn main() -> ExitCode {
+    let result = parse_args(std::env::args().skip(1)).and_then(run);
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
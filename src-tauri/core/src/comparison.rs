@@ -0,0 +1,154 @@
+//! Two periods side by side, e.g. this quarter and the same quarter last
+//! year, for one employee or the whole practice.
+
+use serde::{Serialize, Deserialize};
+use rusqlite::Connection;
+
+use crate::granularity::{self, Day, Granularity};
+use crate::performance::THERAPY_COLUMNS;
+use crate::stats::{self, MonthlyStats};
+use crate::{Error, Result};
+
+/// Inclusive `YYYY-MM-DD` dates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Period {
+    pub from: String,
+    pub to: String,
+}
+
+impl Period {
+    fn of(from: Day, to: Day) -> Self {
+        Period { from: granularity::format_date(from), to: granularity::format_date(to) }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if granularity::parse_date(&self.from)? > granularity::parse_date(&self.to)? {
+            return Err(Error::Invalid(format!("Period {} to {} ends before it starts", self.from, self.to)));
+        }
+        Ok(())
+    }
+}
+
+/// Common comparisons, relative to the period a reference date lies in.
+/// Both periods run to date: from their start to the reference day, and
+/// to the same day in the earlier period. A reference on the last day
+/// compares whole periods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    PreviousMonth,
+    SameMonthLastYear,
+    PreviousQuarter,
+    SameQuarterLastYear,
+    PreviousYear,
+}
+
+impl Preset {
+    /// The current and the earlier period for `reference` (`YYYY-MM-DD`).
+    pub fn periods(self, reference: &str) -> Result<(Period, Period)> {
+        let day = granularity::parse_date(reference)?;
+        let (granularity, months_back) = match self {
+            Preset::PreviousMonth => (Granularity::Month, 1),
+            Preset::SameMonthLastYear => (Granularity::Month, 12),
+            Preset::PreviousQuarter => (Granularity::Quarter, 3),
+            Preset::SameQuarterLastYear => (Granularity::Quarter, 12),
+            Preset::PreviousYear => (Granularity::Year, 12),
+        };
+        let (start, end) = granularity.span(day);
+        let previous_day = granularity::add_months(day, -months_back);
+        let (previous_start, previous_end) = granularity.span(previous_day);
+        let previous_to = if day == end { previous_end } else { previous_day };
+        Ok((Period::of(start, day), Period::of(previous_start, previous_to)))
+    }
+}
+
+/// A figure in both periods.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Delta {
+    pub current: f64,
+    pub previous: f64,
+    /// `current - previous`
+    pub absolute: f64,
+    /// Change relative to `previous` in percent, `None` if that is 0.
+    pub percent: Option<f64>,
+}
+
+impl Delta {
+    pub fn new(current: f64, previous: f64) -> Self {
+        Delta {
+            current,
+            previous,
+            absolute: current - previous,
+            percent: (previous != 0.0).then(|| (current - previous) / previous.abs() * 100.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TherapyDelta {
+    pub therapy_name: String,
+    pub count: Delta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comparison {
+    pub current_period: Period,
+    pub previous_period: Period,
+    /// Totals of each period, labelled `from..to`.
+    pub current: MonthlyStats,
+    pub previous: MonthlyStats,
+    pub income: Delta,
+    pub hours: Delta,
+    pub sick_days: Delta,
+    /// Income minus cost.
+    pub margin: Delta,
+    /// In the order of [`THERAPY_COLUMNS`].
+    pub therapies: Vec<TherapyDelta>,
+}
+
+/// Totals of one employee or, without `employee_id`, of the practice in
+/// `period`. Months only partly in the period cost their share.
+pub fn totals(conn: &Connection, employee_id: Option<i32>, period: &Period) -> Result<MonthlyStats> {
+    period.validate()?;
+    let (from, to) = (Some(period.from.as_str()), Some(period.to.as_str()));
+    let months = match employee_id {
+        Some(id) => stats::stats_by(conn, id, Granularity::Month, from, to, false)?,
+        None => stats::practice_stats_by(conn, Granularity::Month, from, to, false)?,
+    };
+    let mut total = MonthlyStats { month: format!("{}..{}", period.from, period.to), ..Default::default() };
+    for month in &months {
+        total.add(month);
+    }
+    Ok(total)
+}
+
+pub fn compare(conn: &Connection, employee_id: Option<i32>, current: &Period, previous: &Period) -> Result<Comparison> {
+    let now = totals(conn, employee_id, current)?;
+    let before = totals(conn, employee_id, previous)?;
+    let therapies = THERAPY_COLUMNS
+        .iter()
+        .zip(now.therapy_counts().iter().zip(before.therapy_counts()))
+        .map(|((name, _), (now, before))| TherapyDelta {
+            therapy_name: name.to_string(),
+            count: Delta::new(*now as f64, before as f64),
+        })
+        .collect();
+
+    Ok(Comparison {
+        current_period: current.clone(),
+        previous_period: previous.clone(),
+        income: Delta::new(now.generated_income, before.generated_income),
+        hours: Delta::new(now.total_hours, before.total_hours),
+        sick_days: Delta::new(now.sick_days as f64, before.sick_days as f64),
+        margin: Delta::new(now.generated_income - now.cost, before.generated_income - before.cost),
+        therapies,
+        current: now,
+        previous: before,
+    })
+}
+
+/// [`compare`] with the periods of `preset` for `reference` (`YYYY-MM-DD`).
+pub fn compare_preset(conn: &Connection, employee_id: Option<i32>, preset: Preset, reference: &str) -> Result<Comparison> {
+    let (current, previous) = preset.periods(reference)?;
+    compare(conn, employee_id, &current, &previous)
+}
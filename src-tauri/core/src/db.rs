@@ -0,0 +1,45 @@
+use rusqlite::Connection;
+use std::path::Path;
+
+use crate::{encryption, migrations, Result};
+
+/// Opens the database at `path` and makes sure the schema is up to date.
+pub fn open(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    init(&conn)?;
+    Ok(conn)
+}
+
+/// Opens the encrypted database at `path` with `passphrase`, see
+/// [`crate::encryption`].
+pub fn open_encrypted(path: &Path, passphrase: &str) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    encryption::unlock(&conn, passphrase)?;
+    init(&conn)?;
+    Ok(conn)
+}
+
+/// Opens a fresh database that only lives in memory.
+pub fn open_in_memory() -> Result<Connection> {
+    let conn = Connection::open_in_memory()?;
+    init(&conn)?;
+    Ok(conn)
+}
+
+/// Brings the schema up to date, see [`crate::migrations`].
+pub fn init(conn: &Connection) -> Result<()> {
+    migrations::migrate(conn)?;
+    Ok(())
+}
+
+/// Runs `f` inside a transaction. When the caller already has one open,
+/// `f` simply becomes part of it.
+pub fn transaction<T>(conn: &Connection, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+    if !conn.is_autocommit() {
+        return f(conn);
+    }
+    let tx = conn.unchecked_transaction()?;
+    let value = f(&tx)?;
+    tx.commit()?;
+    Ok(value)
+}
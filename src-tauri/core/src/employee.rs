@@ -0,0 +1,111 @@
+use serde::{Serialize, Deserialize};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::{audit, period};
+use crate::journal::Change;
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Employee {
+    pub id: i32,
+    pub name: String,
+    pub join_date: String,
+    pub monthly_rate: f64,
+    pub avg_hours: f64,
+}
+
+/// Editable fields of an employee.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmployeeInput {
+    pub name: String,
+    pub join_date: String,
+    pub monthly_rate: f64,
+    pub avg_hours: f64,
+}
+
+/// Daily hours assumed when an employee has none set.
+pub const DEFAULT_AVG_HOURS: f64 = 8.0;
+
+fn from_row(row: &Row) -> rusqlite::Result<Employee> {
+    Ok(Employee {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        join_date: row.get(2)?,
+        monthly_rate: row.get(3)?,
+        avg_hours: row.get::<_, Option<f64>>(4)?.unwrap_or(DEFAULT_AVG_HOURS),
+    })
+}
+
+/// All employees that are not in the recycle bin.
+pub fn list(conn: &Connection) -> Result<Vec<Employee>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, join_date, monthly_rate, avg_hours FROM employees WHERE deleted_at IS NULL",
+    )?;
+    let employees = stmt.query_map([], from_row)?;
+    Ok(employees.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn get(conn: &Connection, id: i32) -> Result<Employee> {
+    conn.query_row(
+        "SELECT id, name, join_date, monthly_rate, avg_hours FROM employees WHERE id = ?1",
+        params![id],
+        from_row,
+    )
+    .optional()?
+    .ok_or_else(|| Error::NotFound(format!("employee {}", id)))
+}
+
+pub fn name(conn: &Connection, id: i32) -> Result<String> {
+    Ok(get(conn, id)?.name)
+}
+
+pub fn avg_hours(conn: &Connection, id: i32) -> Result<f64> {
+    Ok(get(conn, id)?.avg_hours)
+}
+
+pub fn add(conn: &Connection, employee: &EmployeeInput) -> Result<Change> {
+    audit::audited_insert(conn, "employee", |tx| {
+        tx.execute(
+            "INSERT INTO employees (name, join_date, monthly_rate, avg_hours) VALUES (?1, ?2, ?3, ?4)",
+            params![employee.name, employee.join_date, employee.monthly_rate, employee.avg_hours],
+        )?;
+        Ok(tx.last_insert_rowid())
+    })
+}
+
+/// Changes the fields of an employee. Cost is prorated from the join date,
+/// so it can't be moved across a closed month.
+pub fn update(conn: &Connection, id: i32, employee: &EmployeeInput) -> Result<Change> {
+    let current = get(conn, id)?.join_date;
+    if current != employee.join_date {
+        let (first, last) = if current < employee.join_date {
+            (current.as_str(), employee.join_date.as_str())
+        } else {
+            (employee.join_date.as_str(), current.as_str())
+        };
+        let (first, last) = (first.get(..7).unwrap_or(first), last.get(..7).unwrap_or(last));
+        for closed in period::closed_periods(conn)? {
+            if first <= closed.month.as_str() && closed.month.as_str() <= last {
+                return Err(Error::PeriodClosed(closed.month));
+            }
+        }
+    }
+    audit::audited(conn, "employee", id as i64, "update", |tx| {
+        tx.execute(
+            "UPDATE employees SET name = ?1, join_date = ?2, monthly_rate = ?3, avg_hours = ?4 WHERE id = ?5",
+            params![employee.name, employee.join_date, employee.monthly_rate, employee.avg_hours, id],
+        )?;
+        Ok(())
+    })
+}
+
+/// Moves the employee to the recycle bin.
+pub fn delete(conn: &Connection, id: i32) -> Result<Change> {
+    audit::audited(conn, "employee", id as i64, "delete", |tx| {
+        tx.execute(
+            "UPDATE employees SET deleted_at = datetime('now') WHERE id = ?1 AND deleted_at IS NULL",
+            params![id],
+        )?;
+        Ok(())
+    })
+}
@@ -0,0 +1,97 @@
+//! Encryption of the database file at rest with SQLCipher. Only builds
+//! with the `encryption` feature link SQLCipher; plain SQLite silently
+//! ignores the key pragmas, so every function here refuses to run there.
+
+use rusqlite::{params, Connection};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::{db, migrations};
+use crate::{Error, Result};
+
+/// Whether this build can read and write encrypted databases.
+pub const SUPPORTED: bool = cfg!(feature = "encryption");
+
+/// First bytes of every unencrypted SQLite file.
+const PLAIN_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+fn ensure_supported() -> Result<()> {
+    if !SUPPORTED {
+        return Err(Error::Invalid("This build of the app does not support database encryption".to_string()));
+    }
+    Ok(())
+}
+
+fn ensure_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.is_empty() {
+        return Err(Error::Invalid("The passphrase must not be empty".to_string()));
+    }
+    Ok(())
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> Error {
+    Error::Invalid(format!("{}: {}", path.display(), e))
+}
+
+/// Whether the file at `path` is encrypted, judged by its header. Missing
+/// and empty files are not: SQLite creates them as plain databases.
+pub fn is_encrypted(path: &Path) -> Result<bool> {
+    let mut header = Vec::with_capacity(PLAIN_HEADER.len());
+    match std::fs::File::open(path) {
+        Ok(file) => file.take(PLAIN_HEADER.len() as u64).read_to_end(&mut header).map_err(|e| io_error(path, e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(io_error(path, e)),
+    };
+    Ok(!header.is_empty() && header != PLAIN_HEADER)
+}
+
+/// Sets the key of a freshly opened connection and checks that it fits.
+/// SQLCipher only notices a wrong key on the first read.
+pub fn unlock(conn: &Connection, passphrase: &str) -> Result<()> {
+    ensure_supported()?;
+    conn.pragma_update(None, "key", passphrase)?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map_err(|_| Error::Invalid("Wrong passphrase".to_string()))?;
+    Ok(())
+}
+
+/// Encrypts the plain database at `path` with `passphrase`. The encrypted
+/// copy is written next to it and then moved over the original, so the
+/// file is never left half encrypted.
+pub fn encrypt_in_place(path: &Path, passphrase: &str) -> Result<()> {
+    ensure_supported()?;
+    ensure_passphrase(passphrase)?;
+    if is_encrypted(path)? {
+        return Err(Error::Invalid(format!("{} is already encrypted", path.display())));
+    }
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".encrypting");
+    let partial = PathBuf::from(partial);
+    // Left over from an encryption that was cut off
+    if partial.exists() {
+        std::fs::remove_file(&partial).map_err(|e| io_error(&partial, e))?;
+    }
+
+    {
+        let conn = db::open(path)?;
+        let version = migrations::schema_version(&conn)?;
+        conn.execute(
+            "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+            params![partial.to_string_lossy(), passphrase],
+        )?;
+        conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))?;
+        // sqlcipher_export copies schema and rows, but not the schema version
+        conn.execute_batch(&format!("PRAGMA encrypted.user_version = {}", version))?;
+        conn.execute("DETACH DATABASE encrypted", [])?;
+    }
+    std::fs::rename(&partial, path).map_err(|e| io_error(path, e))?;
+    Ok(())
+}
+
+/// Re-encrypts the database behind the unlocked `conn` with a new passphrase.
+pub fn change_passphrase(conn: &Connection, new_passphrase: &str) -> Result<()> {
+    ensure_supported()?;
+    ensure_passphrase(new_passphrase)?;
+    conn.pragma_update(None, "rekey", new_passphrase)?;
+    Ok(())
+}
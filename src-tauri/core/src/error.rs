@@ -0,0 +1,49 @@
+use std::fmt;
+
+/// Prefix of the message of [`Error::PeriodClosed`].
+/// The frontend can match on it to offer a reopen.
+pub const PERIOD_CLOSED_ERROR: &str = "PERIOD_CLOSED";
+
+#[derive(Debug)]
+pub enum Error {
+    Db(rusqlite::Error),
+    /// An edit touched a month that has been closed.
+    PeriodClosed(String),
+    NotFound(String),
+    Invalid(String),
+    /// The acting user's role doesn't allow this.
+    Forbidden(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Db(e) => write!(f, "{}", e),
+            Error::PeriodClosed(month) => write!(
+                f,
+                "{}: month {} is closed. Reopen it before making changes.",
+                PERIOD_CLOSED_ERROR, month
+            ),
+            Error::NotFound(what) => write!(f, "{} not found", what),
+            Error::Invalid(message) => write!(f, "{}", message),
+            Error::Forbidden(message) => write!(f, "Not allowed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Db(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Db(e)
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Serialize, Deserialize};
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+use crate::employee;
+use crate::performance;
+use crate::stats::MonthlyStats;
+use crate::Result;
+
+const BOM: &str = "\u{feff}";
+
+/// How the CSV is written. German Excel wants [`CsvOptions::german`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    /// Write `1234,5` instead of `1234.5`.
+    pub decimal_comma: bool,
+    /// Start with a UTF-8 byte order mark so Excel detects the encoding.
+    pub bom: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            decimal_comma: false,
+            bom: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn german() -> Self {
+        CsvOptions {
+            delimiter: ';',
+            decimal_comma: true,
+            bom: true,
+        }
+    }
+}
+
+struct CsvWriter<'a> {
+    options: &'a CsvOptions,
+    out: String,
+}
+
+impl<'a> CsvWriter<'a> {
+    fn new(options: &'a CsvOptions) -> Self {
+        let out = if options.bom { BOM.to_string() } else { String::new() };
+        CsvWriter { options, out }
+    }
+
+    fn number(&self, value: f64) -> String {
+        let text = format!("{:.2}", value);
+        if self.options.decimal_comma {
+            text.replace('.', ",")
+        } else {
+            text
+        }
+    }
+
+    fn row<S: AsRef<str>>(&mut self, fields: &[S]) {
+        let delimiter = self.options.delimiter;
+        let line: Vec<String> = fields
+            .iter()
+            .map(|field| {
+                let field = field.as_ref();
+                if field.contains([delimiter, '"', '\n', '\r']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.to_string()
+                }
+            })
+            .collect();
+        self.out.push_str(&line.join(&delimiter.to_string()));
+        self.out.push_str("\r\n");
+    }
+}
+
+/// Raw daily performance records between `from` and `to` (inclusive
+/// `YYYY-MM-DD`), for one employee or all of them.
+pub fn performance_csv(
+    conn: &Connection,
+    employee_id: Option<i32>,
+    from: Option<&str>,
+    to: Option<&str>,
+    options: &CsvOptions,
+) -> Result<String> {
+    let names: HashMap<i32, String> = employee::list(conn)?.into_iter().map(|e| (e.id, e.name)).collect();
+    let records = performance::list_between(conn, employee_id, from, to)?;
+
+    let mut csv = CsvWriter::new(options);
+    let mut header = vec!["employee_id", "employee", "date", "status", "hours_worked", "income"];
+    header.extend(performance::THERAPY_COLUMNS.iter().map(|(_, column)| *column));
+    csv.row(&header);
+
+    for p in &records {
+        let name = match names.get(&p.employee_id) {
+            Some(name) => name.clone(),
+            None => employee::name(conn, p.employee_id)?,
+        };
+        let counts = [
+            p.kg_num, p.mt_num, p.mld_num, p.mld_45_num, p.mld_60_num, p.ma_num, p.fango_num, p.ultraschal_num, p.hb_num,
+        ];
+        let mut fields = vec![
+            p.employee_id.to_string(),
+            name,
+            p.date.clone(),
+            p.status.clone(),
+            csv.number(p.hours_worked),
+            csv.number(p.income),
+        ];
+        fields.extend(counts.iter().map(i32::to_string));
+        csv.row(&fields);
+    }
+    Ok(csv.out)
+}
+
+/// Monthly stats as returned by [`crate::stats::monthly_stats`], one row per month.
+pub fn monthly_stats_csv(stats: &[MonthlyStats], options: &CsvOptions) -> String {
+    let mut csv = CsvWriter::new(options);
+    csv.row(&[
+        "month", "total_hours", "hours_needed", "work_days", "sick_days", "vacation_days", "generated_income",
+        "gross_salary", "employer_cost", "cost", "margin",
+        "total_kg", "total_mt", "total_mld", "total_mld_45", "total_mld_60", "total_ma", "total_fango", "total_ultraschal", "total_hb",
+    ]);
+    for s in stats {
+        let mut fields = vec![
+            s.month.clone(),
+            csv.number(s.total_hours),
+            csv.number(s.hours_needed),
+            s.work_days.to_string(),
+            s.sick_days.to_string(),
+            s.vacation_days.to_string(),
+            csv.number(s.generated_income),
+            csv.number(s.gross_salary),
+            csv.number(s.employer_cost),
+            csv.number(s.cost),
+            csv.number(s.generated_income - s.cost),
+        ];
+        fields.extend(s.therapy_counts().iter().map(i32::to_string));
+        csv.row(&fields);
+    }
+    csv.out
+}
@@ -0,0 +1,5 @@
+//! Exports of performance records and stats for spreadsheets and reports.
+
+pub mod csv;
+pub mod pdf;
+pub mod xlsx;
@@ -0,0 +1,317 @@
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use rusqlite::Connection;
+
+use crate::employee::{self, Employee};
+use crate::performance::THERAPY_COLUMNS;
+use crate::stats::{self, MonthlyStats};
+use crate::Result;
+
+// A4 landscape, in points.
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const MARGIN: f32 = 40.0;
+
+const ROW_HEIGHT: f32 = 14.0;
+const TABLE_FONT_SIZE: f32 = 7.5;
+const MONTH_COLUMN_WIDTH: f32 = 52.0;
+const MONEY_COLUMN_WEIGHT: f32 = 1.6;
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+const BAR_DARK: (f32, f32, f32) = (0.18, 0.40, 0.68);
+const BAR_LIGHT: (f32, f32, f32) = (0.70, 0.78, 0.88);
+
+/// A report of an employee's monthly stats for annual reviews: employee
+/// data, a table per month with totals, and bar charts of hours against
+/// target and income against cost. `start_month` and `end_month` are
+/// inclusive `YYYY-MM`.
+pub fn employee_report_pdf(
+    conn: &Connection,
+    employee_id: i32,
+    start_month: Option<&str>,
+    end_month: Option<&str>,
+) -> Result<Vec<u8>> {
+    let employee = employee::get(conn, employee_id)?;
+    let months = stats::monthly_stats(conn, employee_id, start_month, end_month)?;
+    let period = match (months.first(), months.last()) {
+        (Some(first), Some(last)) => format!("{} to {}", first.month, last.month),
+        _ => "no recorded months".to_string(),
+    };
+
+    let mut pages = Vec::new();
+    let mut page = Page::new();
+    page.text(MARGIN, PAGE_HEIGHT - MARGIN - 10.0, 16.0, true, &format!("Performance report: {}", employee.name));
+    let mut y = employee_header(&mut page, &employee, &period, PAGE_HEIGHT - MARGIN - 34.0);
+
+    let mut total = MonthlyStats { month: "Total".to_string(), ..Default::default() };
+    for s in &months {
+        total.add(s);
+    }
+
+    y = table_header(&mut page, y - 10.0);
+    for s in &months {
+        if y - ROW_HEIGHT < MARGIN + ROW_HEIGHT {
+            pages.push(page);
+            page = Page::new();
+            y = table_header(&mut page, PAGE_HEIGHT - MARGIN);
+        }
+        y = table_row(&mut page, s, y, false);
+    }
+    table_row(&mut page, &total, y, true);
+    pages.push(page);
+
+    let mut charts = Page::new();
+    let chart_height = (PAGE_HEIGHT - 2.0 * MARGIN - 70.0) / 2.0;
+    let upper = MARGIN + chart_height + 50.0;
+    bar_chart(
+        &mut charts,
+        "Hours worked vs target hours",
+        ("Hours", "Target"),
+        &months.iter().map(|s| (s.month.as_str(), s.total_hours, s.hours_needed)).collect::<Vec<_>>(),
+        upper,
+        chart_height,
+    );
+    bar_chart(
+        &mut charts,
+        "Generated income vs cost",
+        ("Income", "Cost"),
+        &months.iter().map(|s| (s.month.as_str(), s.generated_income, s.cost)).collect::<Vec<_>>(),
+        MARGIN + 10.0,
+        chart_height,
+    );
+    pages.push(charts);
+
+    Ok(render(&format!("Performance report {}", employee.name), pages))
+}
+
+/// Writes the employee data below `y` and returns where the next block starts.
+fn employee_header(page: &mut Page, employee: &Employee, period: &str, y: f32) -> f32 {
+    let lines = [
+        ("Employee", format!("{} (#{})", employee.name, employee.id)),
+        ("Joined", employee.join_date.clone()),
+        ("Monthly rate", money(employee.monthly_rate)),
+        ("Hours per day", format!("{:.2}", employee.avg_hours)),
+        ("Period", period.to_string()),
+    ];
+    let mut y = y;
+    for (label, value) in &lines {
+        page.text(MARGIN, y, 9.0, true, label);
+        page.text(MARGIN + 80.0, y, 9.0, false, value);
+        y -= 13.0;
+    }
+    y
+}
+
+fn columns() -> Vec<String> {
+    let mut columns: Vec<String> = ["Hours", "Target", "Attain.", "Work", "Sick", "Vacation"].map(String::from).to_vec();
+    columns.extend(THERAPY_COLUMNS.iter().map(|(name, _)| name.to_uppercase()));
+    columns.extend(["Income", "Cost", "Margin"].map(String::from));
+    columns
+}
+
+/// Right edge of numeric column `i`. The last three hold money and are
+/// wider than the rest.
+fn column_right(i: usize, count: usize) -> f32 {
+    let weight = |column: usize| if column + 3 >= count { MONEY_COLUMN_WEIGHT } else { 1.0 };
+    let unit = (PAGE_WIDTH - 2.0 * MARGIN - MONTH_COLUMN_WIDTH) / (0..count).map(weight).sum::<f32>();
+    MARGIN + MONTH_COLUMN_WIDTH + unit * (0..=i).map(weight).sum::<f32>()
+}
+
+fn table_header(page: &mut Page, y: f32) -> f32 {
+    let columns = columns();
+    page.text(MARGIN, y, TABLE_FONT_SIZE, true, "Month");
+    let width = column_right(1, columns.len()) - column_right(0, columns.len()) - 3.0;
+    for (i, column) in columns.iter().enumerate() {
+        // Long therapy names are set smaller to stay inside their column.
+        let size = TABLE_FONT_SIZE.min(width / text_width(column, 1.0) / 1.05);
+        page.text_right(column_right(i, columns.len()), y, size, true, column);
+    }
+    page.line(MARGIN, y - 4.0, PAGE_WIDTH - MARGIN, y - 4.0);
+    y - ROW_HEIGHT
+}
+
+fn table_row(page: &mut Page, s: &MonthlyStats, y: f32, total: bool) -> f32 {
+    if total {
+        page.line(MARGIN, y + ROW_HEIGHT - 4.0, PAGE_WIDTH - MARGIN, y + ROW_HEIGHT - 4.0);
+    }
+    let attainment = if s.hours_needed > 0.0 {
+        format!("{:.0}%", s.total_hours / s.hours_needed * 100.0)
+    } else {
+        "-".to_string()
+    };
+    let mut cells = vec![
+        format!("{:.2}", s.total_hours),
+        format!("{:.2}", s.hours_needed),
+        attainment,
+        s.work_days.to_string(),
+        s.sick_days.to_string(),
+        s.vacation_days.to_string(),
+    ];
+    cells.extend(s.therapy_counts().iter().map(i32::to_string));
+    cells.extend([money(s.generated_income), money(s.cost), money(s.generated_income - s.cost)]);
+
+    page.text(MARGIN, y, TABLE_FONT_SIZE, total, &s.month);
+    for (i, cell) in cells.iter().enumerate() {
+        page.text_right(column_right(i, cells.len()), y, TABLE_FONT_SIZE, total, cell);
+    }
+    y - ROW_HEIGHT
+}
+
+/// Pairs of bars per month, `(label, first, second)`, in a chart whose
+/// bottom edge is at `y`.
+fn bar_chart(page: &mut Page, title: &str, legend: (&str, &str), values: &[(&str, f64, f64)], y: f32, height: f32) {
+    let left = MARGIN + 50.0;
+    let width = PAGE_WIDTH - MARGIN - left;
+    let plot_bottom = y + 14.0;
+    let plot_height = height - 34.0;
+
+    page.text(MARGIN, y + height - 8.0, 11.0, true, title);
+    let legend_x = PAGE_WIDTH - MARGIN - 150.0;
+    for (i, (label, color)) in [(legend.0, BAR_DARK), (legend.1, BAR_LIGHT)].iter().enumerate() {
+        let x = legend_x + i as f32 * 75.0;
+        page.rect(x, y + height - 9.0, 8.0, 8.0, *color);
+        page.text(x + 12.0, y + height - 8.0, 8.0, false, label);
+    }
+
+    let max = values.iter().map(|(_, a, b)| a.max(*b)).fold(0.0, f64::max);
+    let scale = nice_scale(max);
+    for step in 0..=4 {
+        let value = scale / 4.0 * step as f64;
+        let line_y = plot_bottom + plot_height * step as f32 / 4.0;
+        page.gray_line(left, line_y, left + width, line_y);
+        page.text_right(left - 4.0, line_y - 2.5, 7.0, false, &format!("{:.0}", value));
+    }
+    if values.is_empty() {
+        page.text(left + 10.0, plot_bottom + plot_height / 2.0, 9.0, false, "No data in this period");
+        return;
+    }
+
+    let group = width / values.len() as f32;
+    let bar = (group * 0.35).min(18.0);
+    // Every label needs about 32pt of room.
+    let label_every = (32.0 / group).ceil().max(1.0) as usize;
+    for (i, (label, first, second)) in values.iter().enumerate() {
+        let center = left + group * (i as f32 + 0.5);
+        for (value, x, color) in [(*first, center - bar, BAR_DARK), (*second, center, BAR_LIGHT)] {
+            let bar_height = (value.max(0.0) / scale) as f32 * plot_height;
+            page.rect(x, plot_bottom, bar, bar_height, color);
+        }
+        if i % label_every == 0 {
+            page.text_right(center + text_width(label, 7.0) / 2.0, y, 7.0, false, label);
+        }
+    }
+}
+
+/// Upper bound of a chart axis: `max` rounded up to 1, 2 or 5 times a
+/// power of ten.
+fn nice_scale(max: f64) -> f64 {
+    if max <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(max.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].iter().map(|f| f * magnitude).find(|bound| *bound >= max).unwrap_or(10.0 * magnitude)
+}
+
+fn money(value: f64) -> String {
+    format!("{:.2} €", value)
+}
+
+/// Width of `text` in Helvetica, close enough to right-align numbers.
+fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| match c {
+            '.' | ',' | ' ' | '/' => 278,
+            '-' => 333,
+            '%' => 889,
+            'M' | 'W' | 'm' | 'w' => 833,
+            'i' | 'l' | 'I' | 'j' | 't' | 'f' => 250,
+            'A'..='Z' => 667,
+            'a'..='z' => 520,
+            _ => 556,
+        })
+        .sum();
+    units as f32 / 1000.0 * size
+}
+
+/// Standard fonts only cover WinAnsi, so anything else becomes `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            '€' => 0x80,
+            '–' => 0x96,
+            '—' => 0x97,
+            c if (c as u32) < 0x80 || (0xA0..=0xFF).contains(&(c as u32)) => c as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+struct Page {
+    content: Content,
+}
+
+impl Page {
+    fn new() -> Self {
+        Page { content: Content::new() }
+    }
+
+    fn text(&mut self, x: f32, y: f32, size: f32, bold: bool, text: &str) {
+        let encoded = win_ansi(text);
+        self.content
+            .begin_text()
+            .set_font(if bold { BOLD } else { REGULAR }, size)
+            .next_line(x, y)
+            .show(Str(&encoded))
+            .end_text();
+    }
+
+    fn text_right(&mut self, right: f32, y: f32, size: f32, bold: bool, text: &str) {
+        // Bold digits are as wide as regular ones; letters are a little wider.
+        let width = text_width(text, size) * if bold { 1.05 } else { 1.0 };
+        self.text(right - width, y, size, bold, text);
+    }
+
+    fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.content.set_line_width(0.5).move_to(x1, y1).line_to(x2, y2).stroke();
+    }
+
+    fn gray_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.content.save_state().set_stroke_gray(0.8);
+        self.line(x1, y1, x2, y2);
+        self.content.restore_state();
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, (r, g, b): (f32, f32, f32)) {
+        self.content.save_state().set_fill_rgb(r, g, b).rect(x, y, width, height).fill_nonzero().restore_state();
+    }
+}
+
+fn render(title: &str, pages: Vec<Page>) -> Vec<u8> {
+    let catalog_id = Ref::new(1);
+    let tree_id = Ref::new(2);
+    let regular_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let info_id = Ref::new(5);
+    let page_ids: Vec<(Ref, Ref)> = (0..pages.len() as i32).map(|i| (Ref::new(6 + 2 * i), Ref::new(7 + 2 * i))).collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id).kids(page_ids.iter().map(|(page, _)| *page)).count(pages.len() as i32);
+    pdf.type1_font(regular_id).base_font(Name(b"Helvetica")).encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold_id).base_font(Name(b"Helvetica-Bold")).encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.document_info(info_id).title(TextStr(title)).producer(TextStr("performance-tracker"));
+
+    for (page, (page_id, content_id)) in pages.into_iter().zip(page_ids) {
+        let mut writer = pdf.page(page_id);
+        writer
+            .parent(tree_id)
+            .media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+            .contents(content_id);
+        writer.resources().fonts().pair(REGULAR, regular_id).pair(BOLD, bold_id);
+        writer.finish();
+        pdf.stream(content_id, &page.content.finish());
+    }
+    pdf.finish()
+}
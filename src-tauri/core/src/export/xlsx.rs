@@ -0,0 +1,206 @@
+use rusqlite::Connection;
+use rust_xlsxwriter::utility::{cell_range, row_col_to_cell};
+use rust_xlsxwriter::{ExcelDateTime, Format, FormatAlign, FormatBorder, Workbook, Worksheet, XlsxError};
+
+use crate::employee::{self, Employee};
+use crate::performance::THERAPY_COLUMNS;
+use crate::stats::{self, MonthlyStats};
+use crate::{Error, Result};
+
+const SUMMARY_SHEET: &str = "Summary";
+const FIRST_DATA_ROW: u32 = 3;
+
+// Columns of a stats table.
+const MONTH: u16 = 0;
+const HOURS: u16 = 1;
+const HOURS_NEEDED: u16 = 2;
+const ATTAINMENT: u16 = 3;
+const WORK_DAYS: u16 = 4;
+const FIRST_THERAPY: u16 = 7;
+const INCOME: u16 = FIRST_THERAPY + THERAPY_COLUMNS.len() as u16;
+const COST: u16 = INCOME + 1;
+const MARGIN: u16 = INCOME + 2;
+
+struct Formats {
+    title: Format,
+    header: Format,
+    month: Format,
+    hours: Format,
+    count: Format,
+    percent: Format,
+    currency: Format,
+    total_label: Format,
+}
+
+impl Formats {
+    fn new() -> Self {
+        let currency = "#,##0.00 €";
+        Formats {
+            title: Format::new().set_bold().set_font_size(14),
+            header: Format::new().set_bold().set_border_bottom(FormatBorder::Thin),
+            month: Format::new().set_num_format("mmm yyyy").set_align(FormatAlign::Left),
+            hours: Format::new().set_num_format("0.00"),
+            count: Format::new().set_num_format("0"),
+            percent: Format::new().set_num_format("0.0%"),
+            currency: Format::new().set_num_format(currency),
+            total_label: Format::new().set_bold().set_border_top(FormatBorder::Thin),
+        }
+    }
+
+    /// `format` in bold with the top border of the totals row.
+    fn total(&self, format: &Format) -> Format {
+        format.clone().set_bold().set_border_top(FormatBorder::Thin)
+    }
+}
+
+/// A workbook with a summary sheet for the practice and one sheet per
+/// employee, holding the same numbers as [`stats::monthly_stats`].
+/// `start_month` and `end_month` are inclusive `YYYY-MM`.
+pub fn monthly_stats_xlsx(conn: &Connection, start_month: Option<&str>, end_month: Option<&str>) -> Result<Vec<u8>> {
+    let practice = stats::practice_monthly_stats(conn, start_month, end_month)?;
+    let employees = employee::list(conn)?
+        .into_iter()
+        .map(|e| Ok((stats::monthly_stats(conn, e.id, start_month, end_month)?, e)))
+        .collect::<Result<Vec<_>>>()?;
+    workbook(&practice, &employees).map_err(|e| Error::Invalid(format!("Could not write the workbook: {}", e)))
+}
+
+fn workbook(practice: &[MonthlyStats], employees: &[(Vec<MonthlyStats>, Employee)]) -> Result<Vec<u8>, XlsxError> {
+    let formats = Formats::new();
+    let mut workbook = Workbook::new();
+
+    let names = sheet_names(employees.iter().map(|(_, e)| e));
+    let summary = workbook.add_worksheet().set_name(SUMMARY_SHEET)?;
+    summary.write_string_with_format(0, 0, "Practice", &formats.title)?;
+    let overview_row = stats_table(summary, practice, &formats)? + 3;
+
+    // Totals of each employee, taken from the total row of their sheet.
+    summary.write_string_with_format(overview_row - 1, 0, "Employees", &formats.title)?;
+    let columns = [("Hours", HOURS, &formats.hours), ("Income", INCOME, &formats.currency), ("Cost", COST, &formats.currency)];
+    summary.write_string_with_format(overview_row, 0, "Employee", &formats.header)?;
+    for (i, (title, _, _)) in columns.iter().enumerate() {
+        summary.write_string_with_format(overview_row, i as u16 + 1, *title, &formats.header)?;
+    }
+    summary.write_string_with_format(overview_row, columns.len() as u16 + 1, "Margin", &formats.header)?;
+    let margin_col = columns.len() as u16 + 1;
+
+    let mut row = overview_row + 1;
+    for ((stats, _), name) in employees.iter().zip(&names) {
+        let total_row = FIRST_DATA_ROW + stats.len() as u32;
+        summary.write_string(row, 0, name)?;
+        for (i, (_, column, format)) in columns.iter().enumerate() {
+            let formula = format!("='{}'!{}", name.replace('\'', "''"), row_col_to_cell(total_row, *column));
+            summary.write_formula_with_format(row, i as u16 + 1, formula.as_str(), format)?;
+        }
+        let margin = format!("={}-{}", row_col_to_cell(row, 2), row_col_to_cell(row, 3));
+        summary.write_formula_with_format(row, margin_col, margin.as_str(), &formats.currency)?;
+        row += 1;
+    }
+    summary.write_string_with_format(row, 0, "Total", &formats.total_label)?;
+    for (col, format) in [(1, &formats.hours), (2, &formats.currency), (3, &formats.currency), (4, &formats.currency)] {
+        let formula = sum(overview_row + 1, row, col);
+        summary.write_formula_with_format(row, col, formula.as_str(), &formats.total(format))?;
+    }
+
+    for ((stats, employee), name) in employees.iter().zip(&names) {
+        let sheet = workbook.add_worksheet().set_name(name)?;
+        sheet.write_string_with_format(0, 0, &employee.name, &formats.title)?;
+        sheet.write_string(1, 0, format!("Joined {}", employee.join_date))?;
+        sheet.write_string(1, 2, "Monthly rate")?;
+        sheet.write_number_with_format(1, 4, employee.monthly_rate, &formats.currency)?;
+        stats_table(sheet, stats, &formats)?;
+    }
+
+    workbook.save_to_buffer()
+}
+
+/// Writes the header at row 2, one row per month and a totals row, and
+/// returns the index of the totals row.
+fn stats_table(sheet: &mut Worksheet, stats: &[MonthlyStats], formats: &Formats) -> Result<u32, XlsxError> {
+    let mut headers = vec!["Month", "Hours", "Target hours", "Attainment", "Work days", "Sick days", "Vacation days"];
+    headers.extend(THERAPY_COLUMNS.iter().map(|(name, _)| *name));
+    headers.extend(["Income", "Cost", "Margin"]);
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(FIRST_DATA_ROW - 1, col as u16, header.to_uppercase(), &formats.header)?;
+        sheet.set_column_width(col as u16, if col == MONTH as usize { 11 } else { 10 })?;
+    }
+    sheet.set_column_width(INCOME, 13)?;
+    sheet.set_column_width(COST, 13)?;
+    sheet.set_column_width(MARGIN, 13)?;
+    sheet.set_freeze_panes(FIRST_DATA_ROW, 1)?;
+
+    let mut row = FIRST_DATA_ROW;
+    for s in stats {
+        let (year, month) = s.month.split_once('-').unwrap_or_default();
+        match ExcelDateTime::from_ymd(year.parse().unwrap_or(1900), month.parse().unwrap_or(1), 1) {
+            Ok(date) => sheet.write_datetime_with_format(row, MONTH, &date, &formats.month)?,
+            Err(_) => sheet.write_string(row, MONTH, &s.month)?,
+        };
+        sheet.write_number_with_format(row, HOURS, s.total_hours, &formats.hours)?;
+        sheet.write_number_with_format(row, HOURS_NEEDED, s.hours_needed, &formats.hours)?;
+        for (i, days) in [s.work_days, s.sick_days, s.vacation_days].iter().enumerate() {
+            sheet.write_number_with_format(row, WORK_DAYS + i as u16, *days, &formats.count)?;
+        }
+        for (i, count) in s.therapy_counts().iter().enumerate() {
+            sheet.write_number_with_format(row, FIRST_THERAPY + i as u16, *count, &formats.count)?;
+        }
+        sheet.write_number_with_format(row, INCOME, s.generated_income, &formats.currency)?;
+        sheet.write_number_with_format(row, COST, s.cost, &formats.currency)?;
+        derived_cells(sheet, row, formats, false)?;
+        row += 1;
+    }
+
+    sheet.write_string_with_format(row, MONTH, "Total", &formats.total_label)?;
+    for col in (HOURS..=COST).filter(|col| *col != ATTAINMENT) {
+        let format = match col {
+            HOURS | HOURS_NEEDED => &formats.hours,
+            INCOME | COST => &formats.currency,
+            _ => &formats.count,
+        };
+        let formula = sum(FIRST_DATA_ROW, row, col);
+        sheet.write_formula_with_format(row, col, formula.as_str(), &formats.total(format))?;
+    }
+    derived_cells(sheet, row, formats, true)?;
+    Ok(row)
+}
+
+/// Sum of `col` from `first` up to, but not including, `end`.
+fn sum(first: u32, end: u32, col: u16) -> String {
+    // An empty range would be B4:B3, which Excel reads as B3:B4.
+    if end <= first {
+        return "=0".to_string();
+    }
+    format!("=SUM({})", cell_range(first, col, end - 1, col))
+}
+
+/// Attainment and margin are formulas over the other cells of the row.
+fn derived_cells(sheet: &mut Worksheet, row: u32, formats: &Formats, total: bool) -> Result<(), XlsxError> {
+    let cell = |col| row_col_to_cell(row, col);
+    let attainment = format!("=IF({0}=0,\"\",{1}/{0})", cell(HOURS_NEEDED), cell(HOURS));
+    let margin = format!("={}-{}", cell(INCOME), cell(COST));
+    let (percent, currency) = if total {
+        (formats.total(&formats.percent), formats.total(&formats.currency))
+    } else {
+        (formats.percent.clone(), formats.currency.clone())
+    };
+    sheet.write_formula_with_format(row, ATTAINMENT, attainment.as_str(), &percent)?;
+    sheet.write_formula_with_format(row, MARGIN, margin.as_str(), &currency)?;
+    Ok(())
+}
+
+/// Excel sheet names are at most 31 characters, must be unique and may
+/// not contain `[]:*?/\`.
+fn sheet_names<'a>(employees: impl Iterator<Item = &'a Employee>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for employee in employees {
+        let clean: String = employee.name.chars().filter(|c| !"[]:*?/\\".contains(*c)).collect();
+        let clean = clean.trim().trim_matches('\'');
+        let mut name: String = if clean.is_empty() { format!("Employee {}", employee.id) } else { clean.chars().take(31).collect() };
+        if name.eq_ignore_ascii_case(SUMMARY_SHEET) || names.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+            let suffix = format!(" ({})", employee.id);
+            name = name.chars().take(31 - suffix.len()).collect::<String>() + &suffix;
+        }
+        names.push(name);
+    }
+    names
+}
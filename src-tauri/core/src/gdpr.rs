@@ -0,0 +1,241 @@
+//! Requests of employees as data subjects under the GDPR: an export of
+//! everything stored about one of them, and erasure. Erasure anonymizes the
+//! person but keeps their performance and rates, so practice totals of past
+//! months don't change.
+
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Map, Value};
+use rusqlite::{params, Connection};
+
+use crate::audit::{self, AuditEntry};
+use crate::user::{self, User};
+use crate::personnel_cost::{self, CostComponent};
+use crate::{db, settings};
+use crate::{Error, Result};
+
+/// Marks a JSON file as a data export of one employee.
+pub const ARCHIVE_FORMAT: &str = "performance-tracker-subject-export";
+
+/// How long records about erased employees are kept. Stored in the
+/// settings table under `gdpr.*`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Audit entries about an erased employee are kept anonymized for this
+    /// many years after the change, then deleted.
+    pub audit_years: u32,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        // Retention period of business records under German tax law
+        RetentionConfig { audit_years: 10 }
+    }
+}
+
+pub fn load_config(conn: &Connection) -> Result<RetentionConfig> {
+    let default = RetentionConfig::default();
+    Ok(RetentionConfig {
+        audit_years: settings::get(conn, "gdpr.audit_years")?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default.audit_years),
+    })
+}
+
+pub fn save_config(conn: &Connection, config: &RetentionConfig) -> Result<()> {
+    settings::set(conn, "gdpr.audit_years", &config.audit_years.to_string())
+}
+
+/// The monthly rate and hours of an employee from `date` on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractEntry {
+    /// `YYYY-MM-DD HH:MM:SS` of a change, or `YYYY-MM` of a closed month.
+    pub date: String,
+    pub monthly_rate: f64,
+    pub avg_hours: Option<f64>,
+    /// `audit` for recorded changes, `closed_period` for rates frozen when
+    /// a month was closed.
+    pub source: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubjectArchive {
+    pub format: String,
+    pub created_at: String,
+    /// The employee row as stored, including deleted and erased markers.
+    pub employee: Value,
+    pub contract_history: Vec<ContractEntry>,
+    pub cost_components: Vec<CostComponent>,
+    /// Every performance row, including those in the recycle bin.
+    pub performance: Vec<Map<String, Value>>,
+    pub user_accounts: Vec<User>,
+    pub audit: Vec<AuditEntry>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ErasureSummary {
+    pub audit_anonymized: usize,
+    pub audit_deleted: usize,
+    pub accounts_deleted: usize,
+}
+
+/// Whether `entry` is about the employee: a change of the employee itself
+/// or of one of their performance days or user accounts.
+fn concerns(entry: &AuditEntry, employee_id: i32) -> bool {
+    if entry.entity == "employee" {
+        return entry.entity_id == employee_id as i64;
+    }
+    let owner = |json: &Option<Value>| json.as_ref().and_then(|j| j["employee_id"].as_i64()) == Some(employee_id as i64);
+    matches!(entry.entity.as_str(), "performance" | "user") && (owner(&entry.before) || owner(&entry.after))
+}
+
+fn audit_entries(conn: &Connection, employee_id: i32) -> Result<Vec<AuditEntry>> {
+    let mut entries: Vec<AuditEntry> = audit::query(conn, None, None, None, None)?
+        .into_iter()
+        .filter(|e| concerns(e, employee_id))
+        .collect();
+    entries.reverse();
+    Ok(entries)
+}
+
+fn accounts(conn: &Connection, employee_id: i32) -> Result<Vec<User>> {
+    Ok(user::list(conn)?.into_iter().filter(|u| u.employee_id == Some(employee_id)).collect())
+}
+
+fn contract_history(conn: &Connection, employee_id: i32, audit: &[AuditEntry]) -> Result<Vec<ContractEntry>> {
+    let mut history: Vec<ContractEntry> = Vec::new();
+    for entry in audit.iter().filter(|e| e.entity == "employee") {
+        let Some(after) = &entry.after else { continue };
+        let Some(monthly_rate) = after["monthly_rate"].as_f64() else { continue };
+        let avg_hours = after["avg_hours"].as_f64();
+        if history.last().is_some_and(|h| h.monthly_rate == monthly_rate && h.avg_hours == avg_hours) {
+            continue;
+        }
+        history.push(ContractEntry { date: entry.changed_at.clone(), monthly_rate, avg_hours, source: "audit".to_string() });
+    }
+
+    let mut stmt = conn.prepare("SELECT month, monthly_rate FROM closed_period_rates WHERE employee_id = ?1 ORDER BY month")?;
+    let closed = stmt.query_map(params![employee_id], |row| {
+        Ok(ContractEntry { date: row.get(0)?, monthly_rate: row.get(1)?, avg_hours: None, source: "closed_period".to_string() })
+    })?;
+    history.extend(closed.collect::<rusqlite::Result<Vec<_>>>()?);
+    Ok(history)
+}
+
+/// Everything stored about one employee, for a request for access or
+/// data portability.
+pub fn export(conn: &Connection, employee_id: i32) -> Result<SubjectArchive> {
+    let employee = audit::snapshot(conn, "employee", employee_id as i64)?
+        .ok_or_else(|| Error::NotFound(format!("employee {}", employee_id)))?;
+    let audit = audit_entries(conn, employee_id)?;
+
+    let mut stmt = conn.prepare("SELECT * FROM performance WHERE employee_id = ?1 ORDER BY date, id")?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let performance = stmt
+        .query_map(params![employee_id], |row| audit::row_object(row, &columns))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(SubjectArchive {
+        format: ARCHIVE_FORMAT.to_string(),
+        created_at: conn.query_row("SELECT datetime('now')", [], |row| row.get(0))?,
+        contract_history: contract_history(conn, employee_id, &audit)?,
+        cost_components: personnel_cost::list(conn, employee_id)?,
+        user_accounts: accounts(conn, employee_id)?,
+        employee,
+        performance,
+        audit,
+    })
+}
+
+fn anonymized_name(employee_id: i32) -> String {
+    format!("Erased employee {}", employee_id)
+}
+
+/// Replaces the personal fields of a row snapshot in the audit log.
+fn anonymize_snapshot(json: &mut Option<Value>, employee_id: i32, join_date: &str) {
+    let Some(Value::Object(row)) = json else { return };
+    if row.contains_key("name") {
+        row.insert("name".to_string(), json!(anonymized_name(employee_id)));
+    }
+    if row.contains_key("join_date") {
+        row.insert("join_date".to_string(), json!(join_date));
+    }
+    if row.contains_key("username") {
+        row.insert("username".to_string(), json!(anonymized_name(employee_id)));
+    }
+}
+
+/// Start of the retention period: older audit entries of erased employees
+/// are deleted.
+fn retention_cutoff(conn: &Connection, config: &RetentionConfig) -> Result<String> {
+    Ok(conn.query_row(
+        "SELECT datetime('now', ?1)",
+        params![format!("-{} years", config.audit_years)],
+        |row| row.get(0),
+    )?)
+}
+
+fn purge_employee(conn: &Connection, employee_id: i32, cutoff: &str) -> Result<usize> {
+    let mut purged = 0;
+    for entry in audit_entries(conn, employee_id)?.iter().filter(|e| e.changed_at.as_str() < cutoff) {
+        purged += conn.execute("DELETE FROM audit_log WHERE id = ?1", params![entry.id])?;
+    }
+    Ok(purged)
+}
+
+/// Deletes audit entries about erased employees that are older than the
+/// retention period and returns how many.
+pub fn purge_expired(conn: &Connection, config: &RetentionConfig) -> Result<usize> {
+    let cutoff = retention_cutoff(conn, config)?;
+    let mut stmt = conn.prepare("SELECT id FROM employees WHERE erased_at IS NOT NULL")?;
+    let erased = stmt.query_map([], |row| row.get::<_, i32>(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+    db::transaction(conn, |tx| {
+        erased.iter().map(|id| purge_employee(tx, *id, &cutoff)).sum()
+    })
+}
+
+/// Erases an employee for a request for erasure: their name and user
+/// accounts are removed, and so are their traces in the audit log.
+/// Performance days, rates and income stay, attached to an anonymous
+/// employee, so the practice totals don't change. The join date stays too,
+/// because cost is prorated from it; earlier join dates in the audit log
+/// are replaced by it.
+pub fn erase(conn: &Connection, employee_id: i32, config: &RetentionConfig) -> Result<ErasureSummary> {
+    let employee = audit::snapshot(conn, "employee", employee_id as i64)?
+        .ok_or_else(|| Error::NotFound(format!("employee {}", employee_id)))?;
+    if !employee["erased_at"].is_null() {
+        return Err(Error::Invalid(format!("Employee {} has already been erased", employee_id)));
+    }
+    let join_date = employee["join_date"].as_str().unwrap_or("").to_string();
+
+    db::transaction(conn, |tx| {
+        let mut summary = ErasureSummary {
+            audit_deleted: purge_employee(tx, employee_id, &retention_cutoff(tx, config)?)?,
+            ..Default::default()
+        };
+        let accounts = accounts(tx, employee_id)?;
+        tx.execute(
+            "UPDATE employees SET name = ?1, erased_at = datetime('now') WHERE id = ?2",
+            params![anonymized_name(employee_id), employee_id],
+        )?;
+        summary.accounts_deleted = tx.execute("DELETE FROM users WHERE employee_id = ?1", params![employee_id])?;
+
+        for mut entry in audit_entries(tx, employee_id)? {
+            anonymize_snapshot(&mut entry.before, employee_id, &join_date);
+            anonymize_snapshot(&mut entry.after, employee_id, &join_date);
+            tx.execute(
+                "UPDATE audit_log SET before_json = ?1, after_json = ?2 WHERE id = ?3",
+                params![entry.before.map(|j| j.to_string()), entry.after.map(|j| j.to_string()), entry.id],
+            )?;
+            summary.audit_anonymized += 1;
+        }
+        for account in &accounts {
+            tx.execute(
+                "UPDATE audit_log SET changed_by = ?1 WHERE changed_by = ?2",
+                params![anonymized_name(employee_id), account.username],
+            )?;
+        }
+
+        audit::record(tx, "employee", employee_id as i64, "erase", None, None)?;
+        Ok(summary)
+    })
+}
@@ -0,0 +1,125 @@
+//! The periods stats can be grouped into, and the bit of calendar
+//! arithmetic that needs: which bucket a date falls into and which days a
+//! bucket spans.
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    /// `YYYY-MM-DD`
+    Day,
+    /// ISO week, Monday to Sunday, as `YYYY-Www` with the ISO year.
+    Week,
+    /// `YYYY-MM`
+    #[default]
+    Month,
+    /// `YYYY-Qn`
+    Quarter,
+    /// `YYYY`
+    Year,
+}
+
+/// A date as days since 1970-01-01.
+pub type Day = i64;
+
+/// Parses `YYYY-MM-DD`.
+pub fn parse_date(date: &str) -> Result<Day> {
+    let invalid = || Error::Invalid(format!("Invalid date '{}', expected YYYY-MM-DD", date));
+    let mut parts = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (Some(Some(y)), Some(Some(m)), Some(Some(d))) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    if date.len() != 10 || !(1..=12).contains(&m) || d < 1 || d > days_in_month(y, m) {
+        return Err(invalid());
+    }
+    Ok(days_from_civil(y, m, d))
+}
+
+pub fn format_date(day: Day) -> String {
+    let (y, m, d) = civil_from_days(day);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+pub fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Conversions between civil dates and day numbers after
+// https://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(y: i64, m: i64, d: i64) -> Day {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(day: Day) -> (i64, i64, i64) {
+    let z = day + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (if m <= 2 { yoe + era * 400 + 1 } else { yoe + era * 400 }, m, d)
+}
+
+/// `day` moved by `months` calendar months, on the same day of the month
+/// or the last day of a shorter month.
+pub fn add_months(day: Day, months: i64) -> Day {
+    let (y, m, d) = civil_from_days(day);
+    let index = y * 12 + (m - 1) + months;
+    let (y, m) = (index.div_euclid(12), index.rem_euclid(12) + 1);
+    days_from_civil(y, m, d.min(days_in_month(y, m)))
+}
+
+/// Monday = 0 .. Sunday = 6
+fn weekday(day: Day) -> i64 {
+    (day + 3).rem_euclid(7)
+}
+
+impl Granularity {
+    /// The label of the bucket `day` falls into.
+    pub fn bucket(self, day: Day) -> String {
+        let (y, m, d) = civil_from_days(day);
+        match self {
+            Granularity::Day => format!("{:04}-{:02}-{:02}", y, m, d),
+            Granularity::Week => {
+                // The ISO year is the year of the week's Thursday
+                let thursday = day - weekday(day) + 3;
+                let (iso_year, _, _) = civil_from_days(thursday);
+                let week = (thursday - days_from_civil(iso_year, 1, 1)) / 7 + 1;
+                format!("{:04}-W{:02}", iso_year, week)
+            }
+            Granularity::Month => format!("{:04}-{:02}", y, m),
+            Granularity::Quarter => format!("{:04}-Q{}", y, (m - 1) / 3 + 1),
+            Granularity::Year => format!("{:04}", y),
+        }
+    }
+
+    /// First and last day of the bucket `day` falls into.
+    pub fn span(self, day: Day) -> (Day, Day) {
+        let (y, m, _) = civil_from_days(day);
+        match self {
+            Granularity::Day => (day, day),
+            Granularity::Week => (day - weekday(day), day - weekday(day) + 6),
+            Granularity::Month => (days_from_civil(y, m, 1), days_from_civil(y, m, days_in_month(y, m))),
+            Granularity::Quarter => {
+                let first = (m - 1) / 3 * 3 + 1;
+                (days_from_civil(y, first, 1), days_from_civil(y, first + 2, days_in_month(y, first + 2)))
+            }
+            Granularity::Year => (days_from_civil(y, 1, 1), days_from_civil(y, 12, 31)),
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! CSV exports of other practice software: mapping profiles for their
+//! columns and therapy codes, a preview that checks every row, and the
+//! import itself.
+
+use serde::{Serialize, Deserialize};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::employee;
+use crate::journal::Change;
+use crate::performance::{self, PerformanceInput, THERAPY_COLUMNS};
+use crate::{db, granularity, period, therapy};
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateFormat {
+    /// `2025-01-31`
+    Iso,
+    /// `31.01.2025`
+    German,
+}
+
+/// How the columns and therapy codes of another program's CSV map onto
+/// the performance table. Saved by name in `import_profiles`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MappingProfile {
+    pub name: String,
+    pub delimiter: char,
+    pub decimal_comma: bool,
+    pub date_format: DateFormat,
+    /// Header of the column with the employee's name or id.
+    pub employee_column: String,
+    pub date_column: String,
+    pub therapy_column: String,
+    pub count_column: String,
+    /// Hours of all rows of the same day are added up.
+    pub hours_column: Option<String>,
+    /// Therapy codes of the other program mapped to `therapy_name`s. Codes
+    /// without an entry are matched against the therapy names directly.
+    pub therapies: HashMap<String, String>,
+}
+
+impl Default for MappingProfile {
+    fn default() -> Self {
+        MappingProfile {
+            name: String::new(),
+            delimiter: ';',
+            decimal_comma: true,
+            date_format: DateFormat::German,
+            employee_column: "employee".to_string(),
+            date_column: "date".to_string(),
+            therapy_column: "therapy".to_string(),
+            count_column: "count".to_string(),
+            hours_column: None,
+            therapies: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowError {
+    /// Line in the file, starting at 1 for the header.
+    pub line: usize,
+    pub message: String,
+}
+
+/// One performance day as it will be written by [`apply`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportDay {
+    pub employee_id: i32,
+    pub employee: String,
+    pub date: String,
+    /// The existing record that will be updated, if any.
+    pub performance_id: Option<i32>,
+    pub hours_worked: Option<f64>,
+    /// Counts by performance column. Columns not listed keep their value.
+    pub counts: BTreeMap<String, i32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportPreview {
+    pub days: Vec<ImportDay>,
+    pub errors: Vec<RowError>,
+}
+
+pub fn profiles(conn: &Connection) -> Result<Vec<MappingProfile>> {
+    let mut stmt = conn.prepare("SELECT profile_json FROM import_profiles ORDER BY name")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    rows.map(|json| serde_json::from_str(&json?).map_err(|e| Error::Invalid(e.to_string())))
+        .collect()
+}
+
+pub fn profile(conn: &Connection, name: &str) -> Result<MappingProfile> {
+    let json: String = conn
+        .query_row("SELECT profile_json FROM import_profiles WHERE name = ?1", params![name], |row| row.get(0))
+        .optional()?
+        .ok_or_else(|| Error::NotFound(format!("import profile {}", name)))?;
+    serde_json::from_str(&json).map_err(|e| Error::Invalid(e.to_string()))
+}
+
+/// Saves the profile, replacing one with the same name.
+pub fn save_profile(conn: &Connection, profile: &MappingProfile) -> Result<()> {
+    if profile.name.trim().is_empty() {
+        return Err(Error::Invalid("The import profile needs a name".to_string()));
+    }
+    let json = serde_json::to_string(profile).map_err(|e| Error::Invalid(e.to_string()))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO import_profiles (name, profile_json, updated_at) VALUES (?1, ?2, datetime('now'))",
+        params![profile.name, json],
+    )?;
+    Ok(())
+}
+
+pub fn delete_profile(conn: &Connection, name: &str) -> Result<()> {
+    if conn.execute("DELETE FROM import_profiles WHERE name = ?1", params![name])? == 0 {
+        return Err(Error::NotFound(format!("import profile {}", name)));
+    }
+    Ok(())
+}
+
+/// Characters of Windows-1252 for the bytes 0x80 to 0x9F, where it differs
+/// from Latin-1. The five bytes it leaves undefined keep their C1 control.
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+/// Decodes a file as UTF-8, falling back to Windows-1252 which many older
+/// Windows programs export.
+pub fn decode(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes
+            .iter()
+            .map(|&b| match b {
+                0x80..=0x9f => WINDOWS_1252[(b - 0x80) as usize],
+                _ => b as char,
+            })
+            .collect(),
+    }
+}
+
+/// Splits CSV text into records with the line each starts on.
+/// Quoted fields may contain the delimiter, `""` and line breaks.
+fn records(content: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                if fields.iter().any(|f| !f.trim().is_empty()) {
+                    records.push((start, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                start = line;
+            }
+            '\r' if !quoted => {}
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    fields.push(field);
+    if fields.iter().any(|f| !f.trim().is_empty()) {
+        records.push((start, fields));
+    }
+    records
+}
+
+fn parse_number(text: &str, decimal_comma: bool) -> Option<f64> {
+    let text = text.trim();
+    let text = if decimal_comma { text.replace(',', ".") } else { text.to_string() };
+    text.parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+fn parse_date(text: &str, format: DateFormat) -> Option<String> {
+    let text = text.trim();
+    let parts: Vec<&str> = match format {
+        DateFormat::Iso => text.split('-').collect(),
+        DateFormat::German => text.split('.').rev().collect(),
+    };
+    let [year, month, day] = parts[..] else { return None };
+    let (year, month, day): (u32, u32, u32) = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    if year < 1000 {
+        return None;
+    }
+    // Only dates that exist, so 31.02. is rejected
+    let date = format!("{:04}-{:02}-{:02}", year, month, day);
+    granularity::parse_date(&date).ok()?;
+    Some(date)
+}
+
+/// Parses the file and checks every row against the employees, therapies
+/// and closed months without writing anything. Rows of the same employee
+/// and day are merged into one [`ImportDay`].
+pub fn preview(conn: &Connection, content: &str, profile: &MappingProfile) -> Result<ImportPreview> {
+    let mut rows = records(content, profile.delimiter).into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Err(Error::Invalid("The file is empty".to_string()));
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| Error::Invalid(format!("Column '{}' not found in the file", name)))
+    };
+    let employee_col = column(&profile.employee_column)?;
+    let date_col = column(&profile.date_column)?;
+    let therapy_col = column(&profile.therapy_column)?;
+    let count_col = column(&profile.count_column)?;
+    let hours_col = profile.hours_column.as_deref().map(column).transpose()?;
+
+    let employees = employee::list(conn)?;
+    let therapy_names: Vec<String> = therapy::list(conn)?.into_iter().map(|t| t.therapy_name).collect();
+
+    let mut preview = ImportPreview::default();
+    let mut days: BTreeMap<(String, i32), ImportDay> = BTreeMap::new();
+
+    for (line, fields) in rows {
+        let field = |i: usize| fields.get(i).map_or("", |f| f.trim());
+        let mut fail = |message: String| preview.errors.push(RowError { line, message });
+
+        let who = field(employee_col);
+        let by_id = who.parse::<i32>().ok().and_then(|id| employees.iter().find(|e| e.id == id));
+        let matches: Vec<_> = employees.iter().filter(|e| e.name.trim().eq_ignore_ascii_case(who)).collect();
+        let employee = match (by_id, matches.as_slice()) {
+            (Some(e), _) => e,
+            (None, [e]) => *e,
+            (None, []) => {
+                fail(format!("Unknown employee '{}'", who));
+                continue;
+            }
+            (None, _) => {
+                fail(format!("Employee name '{}' is ambiguous, use the id", who));
+                continue;
+            }
+        };
+
+        let Some(date) = parse_date(field(date_col), profile.date_format) else {
+            fail(format!("Invalid date '{}'", field(date_col)));
+            continue;
+        };
+        if let Err(e) = period::ensure_open(conn, &date) {
+            fail(e.to_string());
+            continue;
+        }
+
+        let code = field(therapy_col);
+        let therapy_name = profile
+            .therapies
+            .iter()
+            .find(|(from, _)| from.trim().eq_ignore_ascii_case(code))
+            .map_or(code, |(_, to)| to.as_str());
+        let Some(therapy_name) = therapy_names.iter().find(|t| t.eq_ignore_ascii_case(therapy_name)) else {
+            fail(format!("Therapy '{}' is not mapped to a therapy", code));
+            continue;
+        };
+        let Some((_, count_column)) = THERAPY_COLUMNS.iter().find(|(name, _)| name == therapy_name) else {
+            fail(format!("Therapy '{}' has no performance column", therapy_name));
+            continue;
+        };
+
+        let count = match parse_number(field(count_col), profile.decimal_comma) {
+            Some(n) if n >= 0.0 && n.fract() == 0.0 => n as i32,
+            _ => {
+                fail(format!("Invalid count '{}'", field(count_col)));
+                continue;
+            }
+        };
+        let hours = match hours_col.map(field).filter(|h| !h.is_empty()) {
+            None => None,
+            Some(h) => match parse_number(h, profile.decimal_comma) {
+                Some(n) if n >= 0.0 => Some(n),
+                _ => {
+                    fail(format!("Invalid hours '{}'", h));
+                    continue;
+                }
+            },
+        };
+
+        let day = days.entry((date.clone(), employee.id)).or_insert_with(|| ImportDay {
+            employee_id: employee.id,
+            employee: employee.name.clone(),
+            date,
+            performance_id: None,
+            hours_worked: None,
+            counts: BTreeMap::new(),
+        });
+        *day.counts.entry(count_column.to_string()).or_insert(0) += count;
+        if let Some(hours) = hours {
+            *day.hours_worked.get_or_insert(0.0) += hours;
+        }
+    }
+
+    for day in days.values_mut() {
+        day.performance_id = performance::list_between(conn, Some(day.employee_id), Some(&day.date), Some(&day.date))?
+            .first()
+            .map(|p| p.id);
+    }
+    preview.days = days.into_values().collect();
+    Ok(preview)
+}
+
+/// Writes the previewed days in one transaction: existing records get the
+/// imported counts and hours, missing days are added as present. Refuses
+/// to import anything while rows have errors.
+pub fn apply(conn: &Connection, content: &str, profile: &MappingProfile) -> Result<Vec<Change>> {
+    let preview = preview(conn, content, profile)?;
+    if !preview.errors.is_empty() {
+        return Err(Error::Invalid(format!(
+            "{} rows have errors. Fix the file or the mapping profile before importing.",
+            preview.errors.len()
+        )));
+    }
+
+    db::transaction(conn, |tx| {
+        let mut changes = Vec::new();
+        for day in &preview.days {
+            let mut input = match day.performance_id {
+                Some(id) => PerformanceInput::from(&performance::get(tx, id)?),
+                None => PerformanceInput {
+                    status: "Present".to_string(),
+                    ..Default::default()
+                },
+            };
+            if let Some(hours) = day.hours_worked {
+                input.hours_worked = hours;
+            }
+            for (column, count) in &day.counts {
+                if let Some(value) = input.count_mut(column) {
+                    *value = *count;
+                }
+            }
+            changes.push(match day.performance_id {
+                Some(id) => performance::update(tx, id, &input)?,
+                None => performance::add(tx, day.employee_id, &day.date, &input)?,
+            });
+        }
+        Ok(changes)
+    })
+}
@@ -0,0 +1,3 @@
+//! Imports of performance data from other software.
+
+pub mod csv;
@@ -0,0 +1,203 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::user::{Permission, User};
+use crate::{audit, db, period, personnel_cost};
+use crate::{Error, Result};
+
+/// How many changes a session can undo.
+pub const JOURNAL_LIMIT: usize = 50;
+
+/// One reversible change: the full row before and after it.
+/// `before` is `None` for inserts, `after` is `None` for deletes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    pub entity: String,
+    pub entity_id: i64,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl Change {
+    pub fn new(entity: &str, entity_id: i64, before: Option<Value>, after: Option<Value>) -> Self {
+        Change {
+            entity: entity.to_string(),
+            entity_id,
+            before,
+            after,
+        }
+    }
+}
+
+/// A change and the user who made it.
+struct Entry {
+    user_id: i32,
+    change: Change,
+}
+
+impl Entry {
+    /// Users undo and redo their own changes; those of others only with
+    /// [`Permission::EditPerformance`].
+    fn allows(&self, user: &User) -> bool {
+        self.user_id == user.id || user.can(Permission::EditPerformance)
+    }
+}
+
+#[derive(Default)]
+struct JournalState {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+}
+
+/// Change journal of one session. It only lives as long as the session
+/// and remembers who made each change.
+#[derive(Default)]
+pub struct Journal {
+    state: Mutex<JournalState>,
+}
+
+impl Journal {
+    /// Records a new change by `user`. Anything that could be redone is
+    /// discarded.
+    pub fn push(&self, user: &User, change: Change) {
+        let mut state = self.state.lock().unwrap();
+        state.redo.clear();
+        state.undo.push_back(Entry { user_id: user.id, change });
+        if state.undo.len() > JOURNAL_LIMIT {
+            state.undo.pop_front();
+        }
+    }
+
+    /// Reverts the most recent change `user` may undo and returns it, or
+    /// `None` if there is nothing left to undo. Changes of other users
+    /// are skipped unless `user` may edit performance.
+    pub fn undo(&self, conn: &Connection, user: &User) -> Result<Option<Change>> {
+        let mut state = self.state.lock().unwrap();
+        let Some(i) = state.undo.iter().rposition(|entry| entry.allows(user)) else {
+            return Ok(None);
+        };
+        user.ensure_change(&state.undo[i].change)?;
+        ensure_not_overlaid(&state.undo[i].change, state.undo.iter().skip(i + 1))?;
+
+        let entry = state.undo.remove(i).expect("index was just found");
+        if let Err(e) = apply(conn, &entry.change, entry.change.before.as_ref(), "undo") {
+            state.undo.insert(i, entry);
+            return Err(e);
+        }
+        let change = entry.change.clone();
+        state.redo.push(entry);
+        Ok(Some(change))
+    }
+
+    /// Forgets all changes, e.g. after a restore replaced the rows they
+    /// refer to.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.undo.clear();
+        state.redo.clear();
+    }
+
+    /// Re-applies the most recently undone change `user` may redo and
+    /// returns it, or `None` if there is nothing to redo.
+    pub fn redo(&self, conn: &Connection, user: &User) -> Result<Option<Change>> {
+        let mut state = self.state.lock().unwrap();
+        let Some(i) = state.redo.iter().rposition(|entry| entry.allows(user)) else {
+            return Ok(None);
+        };
+        user.ensure_change(&state.redo[i].change)?;
+        ensure_not_overlaid(&state.redo[i].change, state.redo[i + 1..].iter())?;
+
+        let entry = state.redo.remove(i);
+        if let Err(e) = apply(conn, &entry.change, entry.change.after.as_ref(), "redo") {
+            state.redo.insert(i, entry);
+            return Err(e);
+        }
+        let change = entry.change.clone();
+        state.undo.push_back(entry);
+        Ok(Some(change))
+    }
+}
+
+pub(crate) fn to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Array(bytes) => SqlValue::Blob(
+            bytes.iter().map(|b| b.as_u64().unwrap_or(0) as u8).collect(),
+        ),
+        Value::Object(_) => SqlValue::Text(value.to_string()),
+    }
+}
+
+/// Puts the row of `entity` back into `state`: deleted when `None`,
+/// otherwise written with exactly the stored columns, id included.
+pub fn restore(conn: &Connection, entity: &str, id: i64, state: Option<&Value>) -> Result<()> {
+    let table = audit::table_for(entity)?;
+    match state {
+        None => {
+            conn.execute(&format!("DELETE FROM {} WHERE id = ?1", table), params![id])?;
+        }
+        Some(Value::Object(row)) => {
+            let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+            let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+            conn.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {} ({}) VALUES ({})",
+                    table,
+                    columns.join(", "),
+                    placeholders.join(", ")
+                ),
+                params_from_iter(row.values().map(to_sql)),
+            )?;
+        }
+        Some(other) => return Err(Error::Invalid(format!("Invalid journal state: {}", other))),
+    }
+    Ok(())
+}
+
+/// Performance rows and cost components of closed months can't be changed
+/// by undo or redo either.
+fn ensure_open(conn: &Connection, change: &Change) -> Result<()> {
+    if change.entity == "cost_component" {
+        return personnel_cost::ensure_open(conn, change.before.as_ref(), change.after.as_ref());
+    }
+    if change.entity != "performance" {
+        return Ok(());
+    }
+    for state in [&change.before, &change.after].into_iter().flatten() {
+        if let Some(date) = state.get("date").and_then(Value::as_str) {
+            period::ensure_open(conn, date)?;
+        }
+    }
+    Ok(())
+}
+
+/// A change can't be reverted past a later change to the same row that
+/// was skipped because it belongs to someone else.
+fn ensure_not_overlaid<'a>(change: &Change, later: impl IntoIterator<Item = &'a Entry>) -> Result<()> {
+    if later.into_iter().any(|entry| entry.change.entity == change.entity && entry.change.entity_id == change.entity_id) {
+        return Err(Error::Invalid(format!(
+            "The {} was changed by someone else since, so this change can't be undone or redone",
+            change.entity.replace('_', " ")
+        )));
+    }
+    Ok(())
+}
+
+fn apply(conn: &Connection, change: &Change, target: Option<&Value>, action: &str) -> Result<()> {
+    ensure_open(conn, change)?;
+    db::transaction(conn, |tx| {
+        let current = audit::snapshot(tx, &change.entity, change.entity_id)?;
+        restore(tx, &change.entity, change.entity_id, target)?;
+        audit::record(tx, &change.entity, change.entity_id, action, current.as_ref(), target)
+    })
+}
@@ -0,0 +1,89 @@
+//! Key figures per worked hour, derived from the monthly stats, with the
+//! practice as a whole for comparison.
+
+use serde::{Serialize, Deserialize};
+use rusqlite::Connection;
+use std::collections::BTreeMap;
+
+use crate::employee;
+use crate::stats::{self, MonthlyStats};
+use crate::Result;
+
+/// Figures of one month. The per-hour figures are `None` without worked
+/// hours.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Kpis {
+    pub month: String,
+    pub revenue_per_hour: Option<f64>,
+    pub cost_per_hour: Option<f64>,
+    /// Revenue minus cost per hour.
+    pub contribution_per_hour: Option<f64>,
+    pub treatments_per_hour: Option<f64>,
+    /// Treatments times the duration of their therapy.
+    pub treatment_minutes: f64,
+    /// The worked hours, in minutes.
+    pub paid_minutes: f64,
+    /// Treatment minutes per paid minute.
+    pub utilization: Option<f64>,
+}
+
+impl Kpis {
+    pub fn from_stats(stats: &MonthlyStats) -> Self {
+        let treatments: i32 = stats.therapy_counts().iter().sum();
+        let paid_minutes = stats.total_hours * 60.0;
+        let per_hour = |value: f64| (stats.total_hours > 0.0).then(|| value / stats.total_hours);
+
+        Kpis {
+            month: stats.month.clone(),
+            revenue_per_hour: per_hour(stats.generated_income),
+            cost_per_hour: per_hour(stats.cost),
+            contribution_per_hour: per_hour(stats.generated_income - stats.cost),
+            treatments_per_hour: per_hour(treatments as f64),
+            treatment_minutes: stats.treatment_minutes,
+            paid_minutes,
+            utilization: stats.utilization,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmployeeKpis {
+    pub employee_id: i32,
+    pub name: String,
+    pub months: Vec<Kpis>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KpiReport {
+    pub employees: Vec<EmployeeKpis>,
+    /// The practice per month, from the summed up stats of every employee,
+    /// so employees with more hours weigh more.
+    pub practice: Vec<Kpis>,
+}
+
+/// KPIs of every employee not in the recycle bin. `start_month` and
+/// `end_month` work as in [`stats::monthly_stats`].
+pub fn monthly_kpis(conn: &Connection, start_month: Option<&str>, end_month: Option<&str>) -> Result<KpiReport> {
+    let mut practice: BTreeMap<String, MonthlyStats> = BTreeMap::new();
+    let mut employees = Vec::new();
+
+    for employee in employee::list(conn)? {
+        let stats = stats::monthly_stats(conn, employee.id, start_month, end_month)?;
+        for month in &stats {
+            practice
+                .entry(month.month.clone())
+                .or_insert_with(|| MonthlyStats { month: month.month.clone(), ..Default::default() })
+                .add(month);
+        }
+        employees.push(EmployeeKpis {
+            employee_id: employee.id,
+            name: employee.name,
+            months: stats.iter().map(Kpis::from_stats).collect(),
+        });
+    }
+
+    Ok(KpiReport {
+        employees,
+        practice: practice.values().map(Kpis::from_stats).collect(),
+    })
+}
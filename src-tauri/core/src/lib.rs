@@ -0,0 +1,33 @@
+//! Business logic of the performance tracker: plain functions over a
+//! SQLite [`rusqlite::Connection`], usable without the Tauri app.
+
+pub mod audit;
+pub mod auto_backup;
+pub mod backup;
+pub mod comparison;
+pub mod db;
+pub mod employee;
+pub mod encryption;
+mod error;
+pub mod export;
+pub mod gdpr;
+pub mod granularity;
+pub mod import;
+pub mod journal;
+pub mod kpi;
+pub mod migrations;
+pub mod performance;
+pub mod personnel_cost;
+pub mod profitability;
+pub mod period;
+pub mod recycle;
+pub mod repository;
+pub mod review;
+pub mod self_service;
+pub mod settings;
+pub mod stats;
+pub mod therapy;
+pub mod transfer;
+pub mod user;
+
+pub use error::{Error, Result, PERIOD_CLOSED_ERROR};
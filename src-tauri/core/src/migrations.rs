@@ -0,0 +1,275 @@
+//! Versioned schema migrations. The version of a database is kept in
+//! SQLite's `user_version`; databases created before migrations existed
+//! report 0, so every step must accept tables that already exist.
+
+use rusqlite::{params, Connection};
+
+use crate::db;
+use crate::{Error, Result};
+
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "employees, performance and therapy tables", apply: initial_schema },
+    Migration { version: 2, description: "rename mdl_* performance columns to mld_*", apply: rename_mdl_columns },
+    Migration { version: 3, description: "closed periods", apply: closed_periods },
+    Migration { version: 4, description: "audit log", apply: audit_log },
+    Migration { version: 5, description: "soft delete", apply: soft_delete },
+    Migration { version: 6, description: "CSV import mapping profiles", apply: import_profiles },
+    Migration { version: 7, description: "settings", apply: settings },
+    Migration { version: 8, description: "user accounts", apply: users },
+    Migration { version: 9, description: "review state of performance days", apply: review_state },
+    Migration { version: 10, description: "review comments", apply: review_comment },
+    Migration { version: 11, description: "erased employees", apply: erased_employees },
+    Migration { version: 12, description: "material cost of closed periods", apply: closed_period_costs },
+    Migration { version: 13, description: "employer cost components", apply: cost_components },
+    Migration { version: 14, description: "therapy durations", apply: therapy_durations },
+    Migration { version: 15, description: "join dates of closed periods", apply: closed_period_join_dates },
+];
+
+/// Version of a database that has every migration applied.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+/// Applies every pending migration and returns the new version.
+pub fn migrate(conn: &Connection) -> Result<u32> {
+    migrate_to(conn, latest_version())
+}
+
+/// Applies pending migrations up to and including `target`.
+pub fn migrate_to(conn: &Connection, target: u32) -> Result<u32> {
+    let current = schema_version(conn)?;
+    if current > latest_version() {
+        return Err(Error::Invalid(format!(
+            "Database schema version {} is newer than this app supports ({})",
+            current,
+            latest_version()
+        )));
+    }
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current && m.version <= target) {
+        db::transaction(conn, |tx| {
+            (migration.apply)(tx)?;
+            tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version))?;
+            Ok(())
+        })?;
+    }
+    schema_version(conn)
+}
+
+pub fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    if !has_column(conn, table, column)? {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
+
+fn initial_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS employees (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            join_date TEXT NOT NULL,
+            monthly_rate REAL NOT NULL,
+            avg_hours REAL DEFAULT 8.0
+        );
+        CREATE TABLE IF NOT EXISTS performance (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL,
+            date TEXT NOT NULL,
+            hours_worked REAL,
+            status TEXT,
+            income REAL,
+            kg_num INTEGER,
+            mt_num INTEGER,
+            mld_num INTEGER,
+            mdl_45_num INTEGER,
+            mdl_60_num INTEGER,
+            ma_num INTEGER,
+            fango_num INTEGER,
+            ultraschal_num INTEGER,
+            hb_num INTEGER,
+            FOREIGN KEY(employee_id) REFERENCES employees(id)
+        );
+        CREATE TABLE IF NOT EXISTS therapy (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            therapy_name TEXT UNIQUE NOT NULL,
+            cost REAL,
+            income REAL
+        );",
+    )?;
+    Ok(())
+}
+
+/// The performance table was created with `mdl_45_num` and `mdl_60_num`,
+/// while the code has always used `mld_45_num` and `mld_60_num`.
+fn rename_mdl_columns(conn: &Connection) -> Result<()> {
+    for (old, new) in [("mdl_45_num", "mld_45_num"), ("mdl_60_num", "mld_60_num")] {
+        if has_column(conn, "performance", old)? && !has_column(conn, "performance", new)? {
+            conn.execute_batch(&format!("ALTER TABLE performance RENAME COLUMN {} TO {}", old, new))?;
+        }
+        add_column(conn, "performance", new, "INTEGER")?;
+    }
+    Ok(())
+}
+
+fn closed_periods(conn: &Connection) -> Result<()> {
+    // Rates and prices as they were when the month was closed, so later
+    // edits to employees or therapies don't change reported numbers.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS closed_periods (
+            month TEXT PRIMARY KEY,
+            closed_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS closed_period_rates (
+            month TEXT NOT NULL,
+            employee_id INTEGER NOT NULL,
+            monthly_rate REAL NOT NULL,
+            PRIMARY KEY(month, employee_id)
+        );
+        CREATE TABLE IF NOT EXISTS closed_period_prices (
+            month TEXT NOT NULL,
+            therapy_name TEXT NOT NULL,
+            income REAL NOT NULL,
+            PRIMARY KEY(month, therapy_name)
+        );
+        CREATE TABLE IF NOT EXISTS period_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            month TEXT NOT NULL,
+            action TEXT NOT NULL,
+            reason TEXT,
+            logged_at TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+fn audit_log(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity TEXT NOT NULL,
+            entity_id INTEGER NOT NULL,
+            action TEXT NOT NULL,
+            before_json TEXT,
+            after_json TEXT,
+            changed_at TEXT NOT NULL,
+            changed_by TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+fn soft_delete(conn: &Connection) -> Result<()> {
+    for table in ["employees", "performance", "therapy"] {
+        add_column(conn, table, "deleted_at", "TEXT")?;
+    }
+    Ok(())
+}
+
+fn import_profiles(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS import_profiles (
+            name TEXT PRIMARY KEY,
+            profile_json TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+fn settings(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+fn users(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS users (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            username TEXT NOT NULL UNIQUE,
+            password_hash TEXT NOT NULL,
+            role TEXT NOT NULL,
+            employee_id INTEGER REFERENCES employees(id),
+            created_at TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+fn review_state(conn: &Connection) -> Result<()> {
+    // Everything entered before self-service was entered by the office
+    add_column(conn, "performance", "review_state", "TEXT NOT NULL DEFAULT 'approved'")
+}
+
+fn review_comment(conn: &Connection) -> Result<()> {
+    add_column(conn, "performance", "review_comment", "TEXT")
+}
+
+fn erased_employees(conn: &Connection) -> Result<()> {
+    add_column(conn, "employees", "erased_at", "TEXT")
+}
+
+/// Months closed before this keep `NULL` and use the current cost.
+fn closed_period_costs(conn: &Connection) -> Result<()> {
+    add_column(conn, "closed_period_prices", "cost", "REAL")
+}
+
+fn cost_components(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS cost_components (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id),
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            amount REAL NOT NULL,
+            valid_from TEXT NOT NULL,
+            valid_to TEXT
+        );",
+    )?;
+    Ok(())
+}
+
+fn therapy_durations(conn: &Connection) -> Result<()> {
+    add_column(conn, "therapy", "duration_minutes", "INTEGER NOT NULL DEFAULT 0")?;
+    // Lymph drainage is sold by its length; other durations are up to the practice
+    conn.execute_batch(
+        "UPDATE therapy SET duration_minutes = 30 WHERE therapy_name = 'mld-30' AND duration_minutes = 0;
+         UPDATE therapy SET duration_minutes = 45 WHERE therapy_name = 'mld-45' AND duration_minutes = 0;
+         UPDATE therapy SET duration_minutes = 60 WHERE therapy_name = 'mld-60' AND duration_minutes = 0;",
+    )?;
+    Ok(())
+}
+
+fn closed_period_join_dates(conn: &Connection) -> Result<()> {
+    add_column(conn, "closed_period_rates", "join_date", "TEXT")?;
+    // The best guess for months closed before is today's join date
+    conn.execute(
+        "UPDATE closed_period_rates
+         SET join_date = (SELECT join_date FROM employees WHERE id = closed_period_rates.employee_id)",
+        [],
+    )?;
+    Ok(())
+}
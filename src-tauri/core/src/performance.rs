@@ -0,0 +1,301 @@
+use serde::{Serialize, Deserialize};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::journal::Change;
+use crate::{audit, granularity, period};
+use crate::{Error, Result};
+
+/// Therapy names in the `therapy` table and the performance column that
+/// counts them.
+pub const THERAPY_COLUMNS: &[(&str, &str)] = &[
+    ("kg", "kg_num"),
+    ("mt", "mt_num"),
+    ("mld-30", "mld_num"),
+    ("mld-45", "mld_45_num"),
+    ("mld-60", "mld_60_num"),
+    ("ma", "ma_num"),
+    ("fango", "fango_num"),
+    ("ultraschal", "ultraschal_num"),
+    ("hb", "hb_num"),
+];
+
+/// Where a performance day is in the review by the office. Days the
+/// office enters are approved right away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewState {
+    /// Entered or changed by the therapist, not yet handed in.
+    Draft,
+    /// Handed in by the therapist, waiting for the office.
+    Submitted,
+    Approved,
+    /// Sent back by the office with a comment.
+    Rejected,
+}
+
+impl ReviewState {
+    const ALL: [ReviewState; 4] = [ReviewState::Draft, ReviewState::Submitted, ReviewState::Approved, ReviewState::Rejected];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReviewState::Draft => "draft",
+            ReviewState::Submitted => "submitted",
+            ReviewState::Approved => "approved",
+            ReviewState::Rejected => "rejected",
+        }
+    }
+
+    fn parse(text: &str) -> Option<ReviewState> {
+        Self::ALL.into_iter().find(|s| s.as_str() == text)
+    }
+
+    /// The transitions of the review. Rejected days the therapist changes
+    /// go back to draft; approved days only when the office reopens them.
+    pub fn can_become(self, next: ReviewState) -> bool {
+        use ReviewState::*;
+        matches!(
+            (self, next),
+            (Draft | Rejected, Submitted) | (Submitted, Approved | Rejected) | (Rejected | Approved, Draft)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Performance {
+    pub id: i32,
+    pub employee_id: i32,
+    pub date: String,
+    pub hours_worked: f64,
+    pub status: String,
+    pub income: f64,
+    pub kg_num: i32,
+    pub mt_num: i32,
+    pub mld_num: i32,
+    pub mld_45_num: i32,
+    pub mld_60_num: i32,
+    pub ma_num: i32,
+    pub fango_num: i32,
+    pub ultraschal_num: i32,
+    pub hb_num: i32,
+    pub review_state: ReviewState,
+    /// Why the office rejected the day, kept until it is approved.
+    pub review_comment: Option<String>,
+}
+
+/// Editable fields of a performance day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PerformanceInput {
+    pub hours_worked: f64,
+    pub status: String,
+    pub income: f64,
+    pub kg_num: i32,
+    pub mt_num: i32,
+    pub mld_num: i32,
+    pub mld_45_num: i32,
+    pub mld_60_num: i32,
+    pub ma_num: i32,
+    pub fango_num: i32,
+    pub ultraschal_num: i32,
+    pub hb_num: i32,
+}
+
+impl PerformanceInput {
+    /// The count stored in `column`, one of the columns of [`THERAPY_COLUMNS`].
+    pub fn count_mut(&mut self, column: &str) -> Option<&mut i32> {
+        match column {
+            "kg_num" => Some(&mut self.kg_num),
+            "mt_num" => Some(&mut self.mt_num),
+            "mld_num" => Some(&mut self.mld_num),
+            "mld_45_num" => Some(&mut self.mld_45_num),
+            "mld_60_num" => Some(&mut self.mld_60_num),
+            "ma_num" => Some(&mut self.ma_num),
+            "fango_num" => Some(&mut self.fango_num),
+            "ultraschal_num" => Some(&mut self.ultraschal_num),
+            "hb_num" => Some(&mut self.hb_num),
+            _ => None,
+        }
+    }
+}
+
+impl Performance {
+    /// Therapy counts in the order of [`THERAPY_COLUMNS`].
+    pub fn therapy_counts(&self) -> [i32; 9] {
+        [
+            self.kg_num,
+            self.mt_num,
+            self.mld_num,
+            self.mld_45_num,
+            self.mld_60_num,
+            self.ma_num,
+            self.fango_num,
+            self.ultraschal_num,
+            self.hb_num,
+        ]
+    }
+}
+
+impl From<&Performance> for PerformanceInput {
+    fn from(p: &Performance) -> Self {
+        PerformanceInput {
+            hours_worked: p.hours_worked,
+            status: p.status.clone(),
+            income: p.income,
+            kg_num: p.kg_num,
+            mt_num: p.mt_num,
+            mld_num: p.mld_num,
+            mld_45_num: p.mld_45_num,
+            mld_60_num: p.mld_60_num,
+            ma_num: p.ma_num,
+            fango_num: p.fango_num,
+            ultraschal_num: p.ultraschal_num,
+            hb_num: p.hb_num,
+        }
+    }
+}
+
+pub(crate) const COLUMNS: &str = "id, employee_id, date, hours_worked, status, income, kg_num, mt_num, mld_num, mld_45_num, mld_60_num, ma_num, fango_num, ultraschal_num, hb_num, review_state, review_comment";
+
+pub(crate) fn from_row(row: &Row) -> rusqlite::Result<Performance> {
+    let count = |i: usize| -> rusqlite::Result<i32> { Ok(row.get::<_, Option<i32>>(i)?.unwrap_or(0)) };
+    Ok(Performance {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        date: row.get(2)?,
+        hours_worked: row.get::<_, Option<f64>>(3)?.unwrap_or(0.0),
+        status: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+        income: row.get::<_, Option<f64>>(5)?.unwrap_or(0.0),
+        kg_num: count(6)?,
+        mt_num: count(7)?,
+        mld_num: count(8)?,
+        mld_45_num: count(9)?,
+        mld_60_num: count(10)?,
+        ma_num: count(11)?,
+        fango_num: count(12)?,
+        ultraschal_num: count(13)?,
+        hb_num: count(14)?,
+        review_state: ReviewState::parse(&row.get::<_, String>(15)?).unwrap_or(ReviewState::Approved),
+        review_comment: row.get(16)?,
+    })
+}
+
+/// All performance days of an employee that are not in the recycle bin.
+pub fn list(conn: &Connection, employee_id: i32) -> Result<Vec<Performance>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM performance WHERE employee_id = ?1 AND deleted_at IS NULL",
+        COLUMNS
+    ))?;
+    let performances = stmt.query_map(params![employee_id], from_row)?;
+    Ok(performances.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Performance days between `from` and `to` (inclusive `YYYY-MM-DD`),
+/// ordered by date. `None` leaves the employee or a bound open.
+pub fn list_between(
+    conn: &Connection,
+    employee_id: Option<i32>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<Performance>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM performance
+         WHERE deleted_at IS NULL
+           AND (?1 IS NULL OR employee_id = ?1)
+           AND (?2 IS NULL OR date >= ?2)
+           AND (?3 IS NULL OR date <= ?3)
+         ORDER BY date ASC, employee_id ASC",
+        COLUMNS
+    ))?;
+    let performances = stmt.query_map(params![employee_id, from, to], from_row)?;
+    Ok(performances.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn get(conn: &Connection, id: i32) -> Result<Performance> {
+    conn.query_row(&format!("SELECT {} FROM performance WHERE id = ?1", COLUMNS), params![id], from_row)
+        .optional()?
+        .ok_or_else(|| Error::NotFound(format!("performance {}", id)))
+}
+
+pub fn add(conn: &Connection, employee_id: i32, date: &str, performance: &PerformanceInput) -> Result<Change> {
+    add_in_state(conn, employee_id, date, performance, ReviewState::Approved)
+}
+
+/// Like [`add`], with the day starting out in review state `state`.
+pub fn add_in_state(
+    conn: &Connection,
+    employee_id: i32,
+    date: &str,
+    performance: &PerformanceInput,
+    state: ReviewState,
+) -> Result<Change> {
+    granularity::parse_date(date)?;
+    period::ensure_open(conn, date)?;
+    let p = performance;
+    audit::audited_insert(conn, "performance", |tx| {
+        tx.execute(
+            "INSERT INTO performance (employee_id, date, hours_worked, status, income, kg_num, mt_num, mld_num, mld_45_num, mld_60_num, ma_num, fango_num, ultraschal_num, hb_num, review_state)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![employee_id, date, p.hours_worked, p.status, p.income, p.kg_num, p.mt_num, p.mld_num, p.mld_45_num, p.mld_60_num, p.ma_num, p.fango_num, p.ultraschal_num, p.hb_num, state.as_str()],
+        )?;
+        Ok(tx.last_insert_rowid())
+    })
+}
+
+/// Changes the fields of a day and keeps its review state.
+pub fn update(conn: &Connection, id: i32, performance: &PerformanceInput) -> Result<Change> {
+    update_in_state(conn, id, performance, None)
+}
+
+/// Like [`update`], moving the day to review state `state` if given.
+pub fn update_in_state(conn: &Connection, id: i32, performance: &PerformanceInput, state: Option<ReviewState>) -> Result<Change> {
+    period::ensure_open(conn, &get(conn, id)?.date)?;
+    let p = performance;
+    audit::audited(conn, "performance", id as i64, "update", |tx| {
+        tx.execute(
+            "UPDATE performance 
+             SET hours_worked = ?1, status = ?2, income = ?3, kg_num = ?4, mt_num = ?5, mld_num = ?6, mld_45_num = ?7, mld_60_num = ?8, ma_num = ?9, fango_num = ?10, ultraschal_num = ?11, hb_num = ?12 
+             WHERE id = ?13",
+            params![p.hours_worked, p.status, p.income, p.kg_num, p.mt_num, p.mld_num, p.mld_45_num, p.mld_60_num, p.ma_num, p.fango_num, p.ultraschal_num, p.hb_num, id],
+        )?;
+        if let Some(state) = state {
+            tx.execute("UPDATE performance SET review_state = ?1 WHERE id = ?2", params![state.as_str(), id])?;
+        }
+        Ok(())
+    })
+}
+
+/// Moves the day to review state `state`, recorded as a review in the
+/// audit log. `comment` replaces the review comment; approval clears it.
+pub fn set_review_state(conn: &Connection, id: i32, state: ReviewState, comment: Option<&str>) -> Result<Change> {
+    let day = get(conn, id)?;
+    period::ensure_open(conn, &day.date)?;
+    if !day.review_state.can_become(state) {
+        return Err(Error::Invalid(format!(
+            "Performance {} is {} and can't become {}",
+            id,
+            day.review_state.as_str(),
+            state.as_str()
+        )));
+    }
+    audit::audited(conn, "performance", id as i64, "review", |tx| {
+        tx.execute(
+            "UPDATE performance
+             SET review_state = ?1,
+                 review_comment = CASE WHEN ?1 = 'approved' THEN NULL ELSE COALESCE(?2, review_comment) END
+             WHERE id = ?3",
+            params![state.as_str(), comment, id],
+        )?;
+        Ok(())
+    })
+}
+
+/// Moves the performance day to the recycle bin.
+pub fn delete(conn: &Connection, id: i32) -> Result<Change> {
+    period::ensure_open(conn, &get(conn, id)?.date)?;
+    audit::audited(conn, "performance", id as i64, "delete", |tx| {
+        tx.execute(
+            "UPDATE performance SET deleted_at = datetime('now') WHERE id = ?1 AND deleted_at IS NULL",
+            params![id],
+        )?;
+        Ok(())
+    })
+}
@@ -0,0 +1,176 @@
+use serde::{Serialize, Deserialize};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+
+use crate::db;
+use crate::{Error, Result};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClosedPeriod {
+    pub month: String,
+    pub closed_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeriodLogEntry {
+    pub id: i32,
+    pub month: String,
+    pub action: String,
+    pub reason: Option<String>,
+    pub logged_at: String,
+}
+
+/// Checks that `month` looks like `YYYY-MM`.
+pub fn validate_month(month: &str) -> Result<()> {
+    let bytes = month.as_bytes();
+    let valid = bytes.len() == 7
+        && bytes[4] == b'-'
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[5..].iter().all(u8::is_ascii_digit)
+        && (1..=12).contains(&month[5..].parse::<u32>().unwrap_or(0));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Invalid(format!("Invalid month '{}', expected YYYY-MM", month)))
+    }
+}
+
+pub fn is_month_closed(conn: &Connection, month: &str) -> Result<bool> {
+    let closed = conn
+        .query_row(
+            "SELECT 1 FROM closed_periods WHERE month = ?1",
+            params![month],
+            |_| Ok(()),
+        )
+        .optional()?;
+    Ok(closed.is_some())
+}
+
+/// Rejects edits to a date (`YYYY-MM-DD`) or month that lies in a closed period.
+pub fn ensure_open(conn: &Connection, date: &str) -> Result<()> {
+    let month = date.get(..7).unwrap_or(date);
+    if is_month_closed(conn, month)? {
+        return Err(Error::PeriodClosed(month.to_string()));
+    }
+    Ok(())
+}
+
+/// Monthly rates frozen for `employee_id`, keyed by month.
+pub fn frozen_rates(conn: &Connection, employee_id: i32) -> Result<HashMap<String, f64>> {
+    let mut stmt = conn.prepare("SELECT month, monthly_rate FROM closed_period_rates WHERE employee_id = ?1")?;
+    let rows = stmt.query_map(params![employee_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+    Ok(rows.collect::<rusqlite::Result<HashMap<_, _>>>()?)
+}
+
+/// Join dates frozen for `employee_id`, keyed by month, since cost is
+/// prorated from them.
+pub fn frozen_join_dates(conn: &Connection, employee_id: i32) -> Result<HashMap<String, String>> {
+    let mut stmt = conn.prepare(
+        "SELECT month, join_date FROM closed_period_rates WHERE employee_id = ?1 AND join_date IS NOT NULL",
+    )?;
+    let rows = stmt.query_map(params![employee_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    Ok(rows.collect::<rusqlite::Result<HashMap<_, _>>>()?)
+}
+
+/// Therapy prices frozen per closed month, keyed by month and then therapy name.
+pub fn frozen_prices(conn: &Connection) -> Result<HashMap<String, HashMap<String, f64>>> {
+    frozen_therapy_values(conn, "income")
+}
+
+/// Material cost of each therapy frozen per closed month, like
+/// [`frozen_prices`]. Months closed before costs were frozen are missing.
+pub fn frozen_costs(conn: &Connection) -> Result<HashMap<String, HashMap<String, f64>>> {
+    frozen_therapy_values(conn, "cost")
+}
+
+fn frozen_therapy_values(conn: &Connection, column: &str) -> Result<HashMap<String, HashMap<String, f64>>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT month, therapy_name, {0} FROM closed_period_prices WHERE {0} IS NOT NULL",
+        column
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, f64>(2)?))
+    })?;
+
+    let mut values: HashMap<String, HashMap<String, f64>> = HashMap::new();
+    for row in rows {
+        let (month, name, value) = row?;
+        values.entry(month).or_default().insert(name, value);
+    }
+    Ok(values)
+}
+
+fn log_action(conn: &Connection, month: &str, action: &str, reason: Option<&str>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO period_log (month, action, reason, logged_at) VALUES (?1, ?2, ?3, datetime('now'))",
+        params![month, action, reason],
+    )?;
+    Ok(())
+}
+
+/// Closes `month`: performance edits are rejected from now on and the
+/// current rates, join dates and prices are frozen for its stats.
+pub fn close_month(conn: &Connection, month: &str) -> Result<()> {
+    validate_month(month)?;
+    if is_month_closed(conn, month)? {
+        return Err(Error::Invalid(format!("Month {} is already closed", month)));
+    }
+
+    db::transaction(conn, |tx| {
+        tx.execute(
+            "INSERT INTO closed_periods (month, closed_at) VALUES (?1, datetime('now'))",
+            params![month],
+        )?;
+        tx.execute(
+            "INSERT INTO closed_period_rates (month, employee_id, monthly_rate, join_date)
+             SELECT ?1, id, monthly_rate, join_date FROM employees",
+            params![month],
+        )?;
+        tx.execute(
+            "INSERT INTO closed_period_prices (month, therapy_name, income, cost)
+             SELECT ?1, therapy_name, COALESCE(income, 0), COALESCE(cost, 0) FROM therapy",
+            params![month],
+        )?;
+        log_action(tx, month, "close", None)
+    })
+}
+
+/// Reopens a closed month and logs why.
+pub fn reopen_month(conn: &Connection, month: &str, reason: Option<&str>) -> Result<()> {
+    validate_month(month)?;
+    if !is_month_closed(conn, month)? {
+        return Err(Error::Invalid(format!("Month {} is not closed", month)));
+    }
+
+    db::transaction(conn, |tx| {
+        for table in ["closed_periods", "closed_period_rates", "closed_period_prices"] {
+            tx.execute(&format!("DELETE FROM {} WHERE month = ?1", table), params![month])?;
+        }
+        log_action(tx, month, "reopen", reason)
+    })
+}
+
+pub fn closed_periods(conn: &Connection) -> Result<Vec<ClosedPeriod>> {
+    let mut stmt = conn.prepare("SELECT month, closed_at FROM closed_periods ORDER BY month ASC")?;
+    let periods = stmt.query_map([], |row| {
+        Ok(ClosedPeriod {
+            month: row.get(0)?,
+            closed_at: row.get(1)?,
+        })
+    })?;
+    Ok(periods.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn period_log(conn: &Connection) -> Result<Vec<PeriodLogEntry>> {
+    let mut stmt = conn.prepare("SELECT id, month, action, reason, logged_at FROM period_log ORDER BY id DESC")?;
+    let entries = stmt.query_map([], |row| {
+        Ok(PeriodLogEntry {
+            id: row.get(0)?,
+            month: row.get(1)?,
+            action: row.get(2)?,
+            reason: row.get(3)?,
+            logged_at: row.get(4)?,
+        })
+    })?;
+    Ok(entries.collect::<rusqlite::Result<Vec<_>>>()?)
+}
@@ -0,0 +1,183 @@
+//! What an employee costs the practice on top of their gross salary:
+//! employer social security contributions, bonuses and benefits, each a
+//! percentage of the salary or a fixed monthly amount valid for a range of
+//! months.
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::{audit, employee, period};
+use crate::journal::Change;
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostKind {
+    /// `amount` percent of the gross salary.
+    Percent,
+    /// `amount` per month.
+    Fixed,
+}
+
+impl CostKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CostKind::Percent => "percent",
+            CostKind::Fixed => "fixed",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Result<Self> {
+        match kind {
+            "percent" => Ok(CostKind::Percent),
+            "fixed" => Ok(CostKind::Fixed),
+            _ => Err(Error::Invalid(format!("Unknown cost kind '{}'", kind))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostComponent {
+    pub id: i32,
+    pub employee_id: i32,
+    pub name: String,
+    pub kind: CostKind,
+    pub amount: f64,
+    /// First month (`YYYY-MM`) the component applies to.
+    pub valid_from: String,
+    /// Last month it applies to, `None` while it has no end.
+    pub valid_to: Option<String>,
+}
+
+/// Editable fields of a cost component.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostComponentInput {
+    pub name: String,
+    pub kind: CostKind,
+    pub amount: f64,
+    pub valid_from: String,
+    pub valid_to: Option<String>,
+}
+
+impl CostComponent {
+    pub fn applies_to(&self, month: &str) -> bool {
+        self.valid_from.as_str() <= month && self.valid_to.as_deref().is_none_or(|to| month <= to)
+    }
+
+    /// What the component costs in `month` for a gross salary of `gross`.
+    pub fn cost(&self, month: &str, gross: f64) -> f64 {
+        match (self.applies_to(month), self.kind) {
+            (false, _) => 0.0,
+            (true, CostKind::Percent) => gross * self.amount / 100.0,
+            (true, CostKind::Fixed) => self.amount,
+        }
+    }
+
+    fn from_json(json: &Value) -> Option<Self> {
+        serde_json::from_value(json.clone()).ok()
+    }
+}
+
+const COLUMNS: &str = "id, employee_id, name, kind, amount, valid_from, valid_to";
+
+fn from_row(row: &Row) -> rusqlite::Result<CostComponent> {
+    let kind: String = row.get(3)?;
+    Ok(CostComponent {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        name: row.get(2)?,
+        kind: CostKind::parse(&kind).unwrap_or(CostKind::Fixed),
+        amount: row.get(4)?,
+        valid_from: row.get(5)?,
+        valid_to: row.get(6)?,
+    })
+}
+
+/// Cost components of one employee, oldest first.
+pub fn list(conn: &Connection, employee_id: i32) -> Result<Vec<CostComponent>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM cost_components WHERE employee_id = ?1 ORDER BY valid_from, id",
+        COLUMNS
+    ))?;
+    let components = stmt.query_map(params![employee_id], from_row)?;
+    Ok(components.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn get(conn: &Connection, id: i32) -> Result<CostComponent> {
+    conn.query_row(&format!("SELECT {} FROM cost_components WHERE id = ?1", COLUMNS), params![id], from_row)
+        .optional()?
+        .ok_or_else(|| Error::NotFound(format!("cost component {}", id)))
+}
+
+/// Employer cost on top of `gross` in `month`.
+pub fn employer_cost(components: &[CostComponent], month: &str, gross: f64) -> f64 {
+    components.iter().fold(0.0, |total, c| total + c.cost(month, gross))
+}
+
+fn validate(input: &CostComponentInput) -> Result<()> {
+    if input.name.trim().is_empty() {
+        return Err(Error::Invalid("A cost component needs a name".to_string()));
+    }
+    if !input.amount.is_finite() || input.amount < 0.0 {
+        return Err(Error::Invalid(format!("Invalid amount {}", input.amount)));
+    }
+    period::validate_month(&input.valid_from)?;
+    if let Some(to) = &input.valid_to {
+        period::validate_month(to)?;
+        if *to < input.valid_from {
+            return Err(Error::Invalid(format!("{} ends before it starts", input.name)));
+        }
+    }
+    Ok(())
+}
+
+/// Rejects a change from `before` to `after` that would change the cost of
+/// a closed month.
+pub(crate) fn ensure_open(conn: &Connection, before: Option<&Value>, after: Option<&Value>) -> Result<()> {
+    let (before, after) = (before.and_then(CostComponent::from_json), after.and_then(CostComponent::from_json));
+    for closed in period::closed_periods(conn)? {
+        // A salary of 100 tells percentages and fixed amounts apart
+        let cost = |c: &Option<CostComponent>| c.as_ref().map_or(0.0, |c| c.cost(&closed.month, 100.0));
+        let kind = |c: &Option<CostComponent>| c.as_ref().filter(|c| c.applies_to(&closed.month)).map(|c| c.kind);
+        if cost(&before) != cost(&after) || kind(&before) != kind(&after) {
+            return Err(Error::PeriodClosed(closed.month));
+        }
+    }
+    Ok(())
+}
+
+pub fn add(conn: &Connection, employee_id: i32, input: &CostComponentInput) -> Result<Change> {
+    validate(input)?;
+    employee::get(conn, employee_id)?;
+    audit::audited_insert(conn, "cost_component", |tx| {
+        tx.execute(
+            "INSERT INTO cost_components (employee_id, name, kind, amount, valid_from, valid_to)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![employee_id, input.name.trim(), input.kind.as_str(), input.amount, input.valid_from, input.valid_to],
+        )?;
+        let id = tx.last_insert_rowid();
+        ensure_open(tx, None, audit::snapshot(tx, "cost_component", id)?.as_ref())?;
+        Ok(id)
+    })
+}
+
+pub fn update(conn: &Connection, id: i32, input: &CostComponentInput) -> Result<Change> {
+    validate(input)?;
+    audit::audited(conn, "cost_component", id as i64, "update", |tx| {
+        let before = audit::snapshot(tx, "cost_component", id as i64)?;
+        tx.execute(
+            "UPDATE cost_components SET name = ?1, kind = ?2, amount = ?3, valid_from = ?4, valid_to = ?5 WHERE id = ?6",
+            params![input.name.trim(), input.kind.as_str(), input.amount, input.valid_from, input.valid_to, id],
+        )?;
+        ensure_open(tx, before.as_ref(), audit::snapshot(tx, "cost_component", id as i64)?.as_ref())
+    })
+}
+
+pub fn delete(conn: &Connection, id: i32) -> Result<Change> {
+    audit::audited(conn, "cost_component", id as i64, "delete", |tx| {
+        ensure_open(tx, audit::snapshot(tx, "cost_component", id as i64)?.as_ref(), None)?;
+        tx.execute("DELETE FROM cost_components WHERE id = ?1", params![id])?;
+        Ok(())
+    })
+}
@@ -0,0 +1,116 @@
+//! Which therapies pay off: treatments delivered, their revenue, material
+//! cost and contribution margin, for the practice and for each employee.
+
+use serde::{Serialize, Deserialize};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+use crate::performance::THERAPY_COLUMNS;
+use crate::{employee, period, therapy};
+use crate::Result;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TherapyProfit {
+    pub therapy_name: String,
+    pub count: i64,
+    pub revenue: f64,
+    /// Material and other direct cost of the treatments.
+    pub cost: f64,
+    /// Revenue minus direct cost.
+    pub contribution_margin: f64,
+    /// Share of this therapy in the revenue of all therapies, 0 to 1.
+    pub revenue_share: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmployeeProfitability {
+    pub employee_id: i32,
+    pub name: String,
+    pub therapies: Vec<TherapyProfit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profitability {
+    /// Every therapy, in the order of [`THERAPY_COLUMNS`].
+    pub practice: Vec<TherapyProfit>,
+    pub employees: Vec<EmployeeProfitability>,
+}
+
+type Prices = HashMap<String, f64>;
+
+/// Per-therapy figures of every employee not in the recycle bin and of the
+/// practice as a whole. `start_month` and `end_month` work as in
+/// [`crate::stats::monthly_stats`]. Closed months use the prices and costs
+/// they were closed with.
+pub fn analyze(conn: &Connection, start_month: Option<&str>, end_month: Option<&str>) -> Result<Profitability> {
+    let (prices, costs) = (therapy::prices(conn)?, therapy::costs(conn)?);
+    let (frozen_prices, frozen_costs) = (period::frozen_prices(conn)?, period::frozen_costs(conn)?);
+    let price = |frozen: &HashMap<String, Prices>, current: &Prices, month: &str, name: &str| {
+        frozen.get(month).unwrap_or(current).get(name).or_else(|| current.get(name)).copied().unwrap_or(0.0)
+    };
+
+    let mut practice = empty();
+    let mut employees = Vec::new();
+    for employee in employee::list(conn)? {
+        let mut therapies = empty();
+        for (month, counts) in monthly_counts(conn, employee.id, start_month, end_month)? {
+            for (((name, _), count), total) in THERAPY_COLUMNS.iter().zip(counts).zip(&mut therapies) {
+                total.count += count;
+                total.revenue += count as f64 * price(&frozen_prices, &prices, &month, name);
+                total.cost += count as f64 * price(&frozen_costs, &costs, &month, name);
+            }
+        }
+        for (total, therapy) in practice.iter_mut().zip(&therapies) {
+            total.count += therapy.count;
+            total.revenue += therapy.revenue;
+            total.cost += therapy.cost;
+        }
+        finish(&mut therapies);
+        employees.push(EmployeeProfitability { employee_id: employee.id, name: employee.name, therapies });
+    }
+    finish(&mut practice);
+    Ok(Profitability { practice, employees })
+}
+
+fn empty() -> Vec<TherapyProfit> {
+    THERAPY_COLUMNS
+        .iter()
+        .map(|(name, _)| TherapyProfit { therapy_name: name.to_string(), ..Default::default() })
+        .collect()
+}
+
+/// Fills in the margin and revenue share once the totals are summed up.
+fn finish(therapies: &mut [TherapyProfit]) {
+    let revenue: f64 = therapies.iter().map(|t| t.revenue).sum();
+    for therapy in therapies {
+        therapy.contribution_margin = therapy.revenue - therapy.cost;
+        therapy.revenue_share = if revenue > 0.0 { therapy.revenue / revenue } else { 0.0 };
+    }
+}
+
+/// Treatments of one employee per month, in the order of [`THERAPY_COLUMNS`].
+fn monthly_counts(
+    conn: &Connection,
+    employee_id: i32,
+    start_month: Option<&str>,
+    end_month: Option<&str>,
+) -> Result<Vec<(String, Vec<i64>)>> {
+    let (start, end) = match (start_month, end_month) {
+        (Some(start), Some(end)) => (Some(start), Some(end)),
+        _ => (None, None),
+    };
+    let sums: Vec<String> = THERAPY_COLUMNS.iter().map(|(_, column)| format!("COALESCE(SUM({}), 0)", column)).collect();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT substr(date, 1, 7) AS month, {}
+         FROM performance
+         WHERE employee_id = ?1 AND deleted_at IS NULL
+           AND (?2 IS NULL OR substr(date, 1, 7) BETWEEN ?2 AND ?3)
+         GROUP BY month",
+        sums.join(", ")
+    ))?;
+    let rows = stmt.query_map(params![employee_id, start, end], |row| {
+        let counts = (1..=THERAPY_COLUMNS.len()).map(|i| row.get(i)).collect::<rusqlite::Result<Vec<i64>>>()?;
+        Ok((row.get(0)?, counts))
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
@@ -0,0 +1,128 @@
+use serde::{Serialize, Deserialize};
+use rusqlite::{params, Connection};
+
+use crate::journal::Change;
+use crate::performance::THERAPY_COLUMNS;
+use crate::{audit, db, period};
+use crate::{Error, Result};
+
+/// Days a deleted item stays in the recycle bin before it may be purged.
+pub const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecycleBinItem {
+    pub entity: String,
+    pub entity_id: i64,
+    pub label: String,
+    pub deleted_at: String,
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<RecycleBinItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT 'employee', id, name, deleted_at FROM employees WHERE deleted_at IS NOT NULL
+         UNION ALL
+         SELECT 'performance', p.id, COALESCE(e.name, '#' || p.employee_id) || ' ' || p.date, p.deleted_at
+         FROM performance p LEFT JOIN employees e ON e.id = p.employee_id
+         WHERE p.deleted_at IS NOT NULL
+         UNION ALL
+         SELECT 'therapy', id, therapy_name, deleted_at FROM therapy WHERE deleted_at IS NOT NULL
+         ORDER BY 4 DESC",
+    )?;
+    let items = stmt.query_map([], |row| {
+        Ok(RecycleBinItem {
+            entity: row.get(0)?,
+            entity_id: row.get(1)?,
+            label: row.get(2)?,
+            deleted_at: row.get(3)?,
+        })
+    })?;
+    Ok(items.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Takes an item back out of the recycle bin.
+pub fn restore(conn: &Connection, entity: &str, id: i64) -> Result<Change> {
+    let table = audit::table_for(entity)?;
+    audit::audited(conn, entity, id, "restore", |tx| {
+        if entity == "performance" {
+            let date: String = tx.query_row("SELECT date FROM performance WHERE id = ?1", params![id], |row| row.get(0))?;
+            period::ensure_open(tx, &date)?;
+        }
+        let restored = tx.execute(
+            &format!("UPDATE {} SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL", table),
+            params![id],
+        )?;
+        if restored == 0 {
+            return Err(Error::NotFound(format!("deleted {} {}", entity, id)));
+        }
+        Ok(())
+    })
+}
+
+/// Permanently removes items that have been in the recycle bin longer than
+/// `retention_days`. Purging an employee also removes their performance rows
+/// and cost components.
+///
+/// Employees with days in closed months and therapies that performance rows
+/// still count stay in the bin, so closed months and the income of past
+/// treatments don't change.
+/// Returns the number of rows removed.
+pub fn purge(conn: &Connection, retention_days: i64) -> Result<usize> {
+    let cutoff = format!("-{} days", retention_days.max(0));
+    db::transaction(conn, |tx| {
+        // Collect everything that expires, employees' remaining records included
+        let mut stmt = tx.prepare(
+            "WITH purged_employees AS (
+                 SELECT id FROM employees e
+                 WHERE deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?1)
+                   AND NOT EXISTS (SELECT 1 FROM performance p
+                                   JOIN closed_periods c ON c.month = substr(p.date, 1, 7)
+                                   WHERE p.employee_id = e.id)
+             )
+             SELECT 'employee', id FROM purged_employees
+             UNION ALL
+             SELECT 'performance', id FROM performance
+             WHERE (deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?1))
+                OR employee_id IN (SELECT id FROM purged_employees)
+             UNION ALL
+             SELECT 'cost_component', id FROM cost_components
+             WHERE employee_id IN (SELECT id FROM purged_employees)
+             UNION ALL
+             SELECT 'therapy', id FROM therapy
+             WHERE deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?1)",
+        )?;
+        let mut expired = stmt
+            .query_map(params![cutoff], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        // Rows referring to an employee go first, therapies after the
+        // performance rows that might still count them
+        let rank = ["performance", "cost_component", "employee", "therapy"];
+        expired.sort_by_key(|(entity, _)| rank.iter().position(|r| r == entity));
+
+        let mut purged = 0;
+        for (entity, id) in &expired {
+            if entity == "therapy" && is_therapy_counted(tx, *id)? {
+                continue;
+            }
+            let before = audit::snapshot(tx, entity, *id)?;
+            tx.execute(&format!("DELETE FROM {} WHERE id = ?1", audit::table_for(entity)?), params![id])?;
+            audit::record(tx, entity, *id, "purge", before.as_ref(), None)?;
+            purged += 1;
+        }
+        Ok(purged)
+    })
+}
+
+/// Whether any performance row, deleted ones included, has treatments of
+/// therapy `id`, whose price it then still needs.
+fn is_therapy_counted(conn: &Connection, id: i64) -> Result<bool> {
+    let name: String = conn.query_row("SELECT therapy_name FROM therapy WHERE id = ?1", params![id], |row| row.get(0))?;
+    let Some((_, column)) = THERAPY_COLUMNS.iter().find(|(therapy, _)| *therapy == name) else {
+        return Ok(false);
+    };
+    let counted = conn.query_row(
+        &format!("SELECT EXISTS (SELECT 1 FROM performance WHERE {} > 0)", column),
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(counted)
+}
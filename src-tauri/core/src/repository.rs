@@ -0,0 +1,123 @@
+//! Repository traits over the stored entities, so callers and tests don't
+//! need to know how the data is kept.
+
+use rusqlite::Connection;
+use std::path::Path;
+
+use crate::employee::{self, Employee, EmployeeInput};
+use crate::journal::Change;
+use crate::performance::{self, Performance, PerformanceInput};
+use crate::stats::{self, MonthlyStats};
+use crate::therapy::{self, Therapy, TherapyInput};
+use crate::{db, Result};
+
+pub trait EmployeeRepository {
+    fn employees(&self) -> Result<Vec<Employee>>;
+    fn employee(&self, id: i32) -> Result<Employee>;
+    fn add_employee(&self, employee: &EmployeeInput) -> Result<Change>;
+    fn update_employee(&self, id: i32, employee: &EmployeeInput) -> Result<Change>;
+    fn delete_employee(&self, id: i32) -> Result<Change>;
+}
+
+pub trait PerformanceRepository {
+    fn performances(&self, employee_id: i32) -> Result<Vec<Performance>>;
+    fn performances_between(&self, employee_id: Option<i32>, from: Option<&str>, to: Option<&str>) -> Result<Vec<Performance>>;
+    fn add_performance(&self, employee_id: i32, date: &str, performance: &PerformanceInput) -> Result<Change>;
+    fn update_performance(&self, id: i32, performance: &PerformanceInput) -> Result<Change>;
+    fn delete_performance(&self, id: i32) -> Result<Change>;
+    fn monthly_stats(&self, employee_id: i32, start_month: Option<&str>, end_month: Option<&str>) -> Result<Vec<MonthlyStats>>;
+}
+
+pub trait TherapyRepository {
+    fn therapies(&self) -> Result<Vec<Therapy>>;
+    fn add_therapy(&self, therapy: &TherapyInput) -> Result<Change>;
+    fn update_therapy(&self, id: i32, therapy: &TherapyInput) -> Result<Change>;
+    fn delete_therapy(&self, id: i32) -> Result<Change>;
+}
+
+/// Repository backed by a SQLite database, either a file or in memory.
+pub struct SqliteRepository {
+    conn: Connection,
+}
+
+impl SqliteRepository {
+    /// Opens (and migrates) the database file at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(SqliteRepository { conn: db::open(path)? })
+    }
+
+    /// A fresh, migrated database that is gone once dropped.
+    pub fn in_memory() -> Result<Self> {
+        Ok(SqliteRepository { conn: db::open_in_memory()? })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl EmployeeRepository for SqliteRepository {
+    fn employees(&self) -> Result<Vec<Employee>> {
+        employee::list(&self.conn)
+    }
+
+    fn employee(&self, id: i32) -> Result<Employee> {
+        employee::get(&self.conn, id)
+    }
+
+    fn add_employee(&self, employee: &EmployeeInput) -> Result<Change> {
+        employee::add(&self.conn, employee)
+    }
+
+    fn update_employee(&self, id: i32, employee: &EmployeeInput) -> Result<Change> {
+        employee::update(&self.conn, id, employee)
+    }
+
+    fn delete_employee(&self, id: i32) -> Result<Change> {
+        employee::delete(&self.conn, id)
+    }
+}
+
+impl PerformanceRepository for SqliteRepository {
+    fn performances(&self, employee_id: i32) -> Result<Vec<Performance>> {
+        performance::list(&self.conn, employee_id)
+    }
+
+    fn performances_between(&self, employee_id: Option<i32>, from: Option<&str>, to: Option<&str>) -> Result<Vec<Performance>> {
+        performance::list_between(&self.conn, employee_id, from, to)
+    }
+
+    fn add_performance(&self, employee_id: i32, date: &str, performance: &PerformanceInput) -> Result<Change> {
+        performance::add(&self.conn, employee_id, date, performance)
+    }
+
+    fn update_performance(&self, id: i32, performance: &PerformanceInput) -> Result<Change> {
+        performance::update(&self.conn, id, performance)
+    }
+
+    fn delete_performance(&self, id: i32) -> Result<Change> {
+        performance::delete(&self.conn, id)
+    }
+
+    fn monthly_stats(&self, employee_id: i32, start_month: Option<&str>, end_month: Option<&str>) -> Result<Vec<MonthlyStats>> {
+        stats::monthly_stats(&self.conn, employee_id, start_month, end_month)
+    }
+}
+
+impl TherapyRepository for SqliteRepository {
+    fn therapies(&self) -> Result<Vec<Therapy>> {
+        therapy::list(&self.conn)
+    }
+
+    fn add_therapy(&self, therapy: &TherapyInput) -> Result<Change> {
+        therapy::add(&self.conn, therapy)
+    }
+
+    fn update_therapy(&self, id: i32, therapy: &TherapyInput) -> Result<Change> {
+        therapy::update(&self.conn, id, therapy)
+    }
+
+    fn delete_therapy(&self, id: i32) -> Result<Change> {
+        therapy::delete(&self.conn, id)
+    }
+}
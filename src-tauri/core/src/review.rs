@@ -0,0 +1,49 @@
+//! The office's side of self-service: days therapists submitted and their
+//! approval or rejection, see [`ReviewState`].
+
+use rusqlite::{params, Connection};
+
+use crate::db;
+use crate::journal::Change;
+use crate::performance::{self, Performance, ReviewState};
+use crate::{Error, Result};
+
+/// Submitted days of one employee or, without `employee_id`, of everyone,
+/// ordered by date.
+pub fn pending(conn: &Connection, employee_id: Option<i32>) -> Result<Vec<Performance>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM performance
+         WHERE deleted_at IS NULL AND review_state = ?1 AND (?2 IS NULL OR employee_id = ?2)
+         ORDER BY date ASC, employee_id ASC",
+        performance::COLUMNS
+    ))?;
+    let days = stmt.query_map(params![ReviewState::Submitted.as_str(), employee_id], performance::from_row)?;
+    Ok(days.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Moves every day in `ids` to `state` in one transaction. Nothing changes
+/// if any of them can't.
+fn review_all(conn: &Connection, ids: &[i32], state: ReviewState, comment: Option<&str>) -> Result<Vec<Change>> {
+    db::transaction(conn, |tx| {
+        ids.iter().map(|id| performance::set_review_state(tx, *id, state, comment)).collect()
+    })
+}
+
+/// Approves submitted days.
+pub fn approve(conn: &Connection, ids: &[i32]) -> Result<Vec<Change>> {
+    review_all(conn, ids, ReviewState::Approved, None)
+}
+
+/// Sends submitted days back to the therapist with `comment`.
+pub fn reject(conn: &Connection, ids: &[i32], comment: &str) -> Result<Vec<Change>> {
+    if comment.trim().is_empty() {
+        return Err(Error::Invalid("Tell the therapist why the days were rejected".to_string()));
+    }
+    review_all(conn, ids, ReviewState::Rejected, Some(comment.trim()))
+}
+
+/// Hands approved days back to the therapist as drafts, e.g. to correct
+/// them. They no longer count as approved until submitted and approved again.
+pub fn reopen(conn: &Connection, ids: &[i32]) -> Result<Vec<Change>> {
+    review_all(conn, ids, ReviewState::Draft, None)
+}
@@ -0,0 +1,143 @@
+//! What a therapist sees of their own work: their performance days and a
+//! monthly summary without anything derived from salaries. Days they enter
+//! or change are drafts until they submit a week or month to the office.
+
+use serde::{Serialize, Deserialize};
+use rusqlite::Connection;
+
+use crate::granularity::{self, Day, Granularity};
+use crate::{db, period};
+use crate::journal::Change;
+use crate::performance::{self, Performance, PerformanceInput, ReviewState};
+use crate::stats::{self, MonthlyStats};
+use crate::user::User;
+use crate::{Error, Result};
+
+/// [`MonthlyStats`] without the cost, which is the employee's salary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnMonthlyStats {
+    pub month: String,
+    pub total_hours: f64,
+    pub hours_needed: f64,
+    pub work_days: i32,
+    pub sick_days: i32,
+    pub vacation_days: i32,
+    pub generated_income: f64,
+    pub total_kg: i32,
+    pub total_mt: i32,
+    pub total_mld: i32,
+    pub total_mld_45: i32,
+    pub total_mld_60: i32,
+    pub total_ma: i32,
+    pub total_fango: i32,
+    pub total_ultraschal: i32,
+    pub total_hb: i32,
+    pub treatment_minutes: f64,
+    pub utilization: Option<f64>,
+}
+
+impl From<&MonthlyStats> for OwnMonthlyStats {
+    fn from(s: &MonthlyStats) -> Self {
+        OwnMonthlyStats {
+            month: s.month.clone(),
+            total_hours: s.total_hours,
+            hours_needed: s.hours_needed,
+            work_days: s.work_days,
+            sick_days: s.sick_days,
+            vacation_days: s.vacation_days,
+            generated_income: s.generated_income,
+            total_kg: s.total_kg,
+            total_mt: s.total_mt,
+            total_mld: s.total_mld,
+            total_mld_45: s.total_mld_45,
+            total_mld_60: s.total_mld_60,
+            total_ma: s.total_ma,
+            total_fango: s.total_fango,
+            total_ultraschal: s.total_ultraschal,
+            total_hb: s.total_hb,
+            treatment_minutes: s.treatment_minutes,
+            utilization: s.utilization,
+        }
+    }
+}
+
+fn own_employee(user: &User) -> Result<i32> {
+    user.employee_id
+        .ok_or_else(|| Error::Forbidden(format!("{} is not linked to an employee", user.username)))
+}
+
+/// The user's own day `id`.
+fn own_day(conn: &Connection, user: &User, id: i32) -> Result<Performance> {
+    let day = performance::get(conn, id)?;
+    if day.employee_id != own_employee(user)? {
+        return Err(Error::Forbidden(format!("{} may only change their own days", user.username)));
+    }
+    Ok(day)
+}
+
+pub fn own_performance(conn: &Connection, user: &User) -> Result<Vec<Performance>> {
+    performance::list(conn, own_employee(user)?)
+}
+
+/// Monthly summary of the user's own days. `start_month` and `end_month`
+/// work as in [`stats::monthly_stats`].
+pub fn own_monthly_stats(
+    conn: &Connection,
+    user: &User,
+    start_month: Option<&str>,
+    end_month: Option<&str>,
+) -> Result<Vec<OwnMonthlyStats>> {
+    let monthly = stats::monthly_stats(conn, own_employee(user)?, start_month, end_month)?;
+    Ok(monthly.iter().map(OwnMonthlyStats::from).collect())
+}
+
+/// Enters a day for the user as a draft.
+pub fn enter_day(conn: &Connection, user: &User, date: &str, input: &PerformanceInput) -> Result<Change> {
+    performance::add_in_state(conn, own_employee(user)?, date, input, ReviewState::Draft)
+}
+
+/// Changes one of the user's days that is not with the office, which makes
+/// a rejected day a draft again.
+pub fn update_day(conn: &Connection, user: &User, id: i32, input: &PerformanceInput) -> Result<Change> {
+    if matches!(own_day(conn, user, id)?.review_state, ReviewState::Submitted | ReviewState::Approved) {
+        return Err(Error::Invalid("Submitted and approved days can only be changed by the office".to_string()));
+    }
+    performance::update_in_state(conn, id, input, Some(ReviewState::Draft))
+}
+
+/// Deletes one of the user's days that is not with the office.
+pub fn delete_day(conn: &Connection, user: &User, id: i32) -> Result<Change> {
+    if matches!(own_day(conn, user, id)?.review_state, ReviewState::Submitted | ReviewState::Approved) {
+        return Err(Error::Invalid("Submitted and approved days can only be deleted by the office".to_string()));
+    }
+    performance::delete(conn, id)
+}
+
+/// Submits the user's drafts and rejected days from `from` to `to`
+/// (inclusive `YYYY-MM-DD`).
+pub fn submit_range(conn: &Connection, user: &User, from: &str, to: &str) -> Result<Vec<Change>> {
+    let employee_id = own_employee(user)?;
+    db::transaction(conn, |tx| {
+        let days = performance::list_between(tx, Some(employee_id), Some(from), Some(to))?;
+        days.iter()
+            .filter(|day| matches!(day.review_state, ReviewState::Draft | ReviewState::Rejected))
+            .map(|day| performance::set_review_state(tx, day.id, ReviewState::Submitted, None))
+            .collect()
+    })
+}
+
+/// Submits the week, Monday to Sunday, that contains `date`.
+pub fn submit_week(conn: &Connection, user: &User, date: &str) -> Result<Vec<Change>> {
+    submit_span(conn, user, Granularity::Week, granularity::parse_date(date)?)
+}
+
+/// Submits the month `YYYY-MM`.
+pub fn submit_month(conn: &Connection, user: &User, month: &str) -> Result<Vec<Change>> {
+    period::validate_month(month)?;
+    submit_span(conn, user, Granularity::Month, granularity::parse_date(&format!("{}-01", month))?)
+}
+
+fn submit_span(conn: &Connection, user: &User, granularity: Granularity, day: Day) -> Result<Vec<Change>> {
+    let (first, last) = granularity.span(day);
+    submit_range(conn, user, &granularity::format_date(first), &granularity::format_date(last))
+}
@@ -0,0 +1,376 @@
+use serde::{Serialize, Deserialize};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::granularity::{self, Day, Granularity};
+use crate::{employee, period, personnel_cost, therapy};
+use crate::Result;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonthlyStats {
+    /// `YYYY-MM`, or the label of the bucket for [`stats_by`].
+    pub month: String,
+    pub total_hours: f64,
+    pub work_days: i32,
+    pub sick_days: i32,
+    pub vacation_days: i32,
+    pub gross_salary: f64,    // employee monthly rate
+    pub employer_cost: f64,   // contributions, bonuses and benefits on top
+    pub cost: f64,            // gross salary plus employer cost
+    pub generated_income: f64, // income generated from therapies
+    pub total_kg: i32,
+    pub total_mt: i32,
+    pub total_mld: i32,
+    pub total_mld_45: i32,
+    pub total_mld_60: i32,
+    pub total_ma: i32,
+    pub total_fango: i32,
+    pub total_ultraschal: i32,
+    pub total_hb: i32,
+    pub hours_needed: f64,
+    /// Treatments times the duration of their therapy.
+    pub treatment_minutes: f64,
+    /// Treatment time per worked time, `None` without worked hours.
+    pub utilization: Option<f64>,
+}
+
+impl MonthlyStats {
+    /// Adds the totals of `other` (usually another employee in the same
+    /// month) to these.
+    pub fn add(&mut self, other: &MonthlyStats) {
+        self.total_hours += other.total_hours;
+        self.work_days += other.work_days;
+        self.sick_days += other.sick_days;
+        self.vacation_days += other.vacation_days;
+        self.gross_salary += other.gross_salary;
+        self.employer_cost += other.employer_cost;
+        self.cost += other.cost;
+        self.generated_income += other.generated_income;
+        self.total_kg += other.total_kg;
+        self.total_mt += other.total_mt;
+        self.total_mld += other.total_mld;
+        self.total_mld_45 += other.total_mld_45;
+        self.total_mld_60 += other.total_mld_60;
+        self.total_ma += other.total_ma;
+        self.total_fango += other.total_fango;
+        self.total_ultraschal += other.total_ultraschal;
+        self.total_hb += other.total_hb;
+        self.hours_needed += other.hours_needed;
+        self.treatment_minutes += other.treatment_minutes;
+        self.utilization = utilization(self.treatment_minutes, self.total_hours);
+    }
+
+    /// Therapy counts in the order of [`crate::performance::THERAPY_COLUMNS`].
+    pub fn therapy_counts(&self) -> [i32; 9] {
+        [
+            self.total_kg,
+            self.total_mt,
+            self.total_mld,
+            self.total_mld_45,
+            self.total_mld_60,
+            self.total_ma,
+            self.total_fango,
+            self.total_ultraschal,
+            self.total_hb,
+        ]
+    }
+}
+
+/// Per-month totals of one employee. When both `start_month` and
+/// `end_month` (`YYYY-MM`, inclusive) are given only that range is returned.
+pub fn monthly_stats(
+    conn: &Connection,
+    employee_id: i32,
+    start_month: Option<&str>,
+    end_month: Option<&str>,
+) -> Result<Vec<MonthlyStats>> {
+    monthly_stats_filtered(conn, employee_id, start_month, end_month, false)
+}
+
+/// Like [`monthly_stats`]; with `approved_only` days still in review
+/// are left out.
+pub fn monthly_stats_filtered(
+    conn: &Connection,
+    employee_id: i32,
+    start_month: Option<&str>,
+    end_month: Option<&str>,
+    approved_only: bool,
+) -> Result<Vec<MonthlyStats>> {
+    let (from, to) = month_range(start_month, end_month)?;
+    stats_by(conn, employee_id, Granularity::Month, from.as_deref(), to.as_deref(), approved_only)
+}
+
+/// First and last day of the months `start_month` to `end_month`, when both
+/// are given.
+pub fn month_range(start_month: Option<&str>, end_month: Option<&str>) -> Result<(Option<String>, Option<String>)> {
+    match (start_month, end_month) {
+        (Some(start), Some(end)) => {
+            period::validate_month(start)?;
+            period::validate_month(end)?;
+            let last = Granularity::Month.span(granularity::parse_date(&format!("{}-01", end))?).1;
+            Ok((Some(format!("{}-01", start)), Some(granularity::format_date(last))))
+        }
+        _ => Ok((None, None)),
+    }
+}
+
+/// Totals of one employee per day, week, month, quarter or year, labelled
+/// as described at [`Granularity`]. `from` and `to` are inclusive
+/// `YYYY-MM-DD` dates. A bucket's cost is the share of the monthly cost for
+/// the days of the bucket within `from` and `to` and since the join date, so
+/// weeks get a part of one or two months and quarters up to three months.
+/// Months without any recorded day cost nothing, like they have no bucket
+/// by month, so quarters and years add up to their months.
+pub fn stats_by(
+    conn: &Connection,
+    employee_id: i32,
+    granularity: Granularity,
+    from: Option<&str>,
+    to: Option<&str>,
+    approved_only: bool,
+) -> Result<Vec<MonthlyStats>> {
+    let therapy_prices = therapy::prices(conn)?;
+    let durations = therapy::durations(conn)?;
+
+    let (emp_rate, avg_hours, join_date) = conn
+        .query_row(
+            "SELECT monthly_rate, avg_hours, join_date FROM employees WHERE id = ?1",
+            params![employee_id],
+            |row| Ok((row.get::<_, f64>(0)?, row.get::<_, Option<f64>>(1)?, row.get::<_, Option<String>>(2)?)),
+        )
+        .optional()?
+        .map(|(rate, hours, join_date)| (rate, hours.unwrap_or(employee::DEFAULT_AVG_HOURS), join_date))
+        .unwrap_or((0.0, employee::DEFAULT_AVG_HOURS, None)); // employee not found
+    let joined = join_date.and_then(|date| granularity::parse_date(&date).ok());
+
+    // Closed months keep the rate, join date and prices they were reported with
+    let frozen_rates = period::frozen_rates(conn, employee_id)?;
+    let frozen_joined = period::frozen_join_dates(conn, employee_id)?
+        .into_iter()
+        .filter_map(|(month, date)| Some((month, granularity::parse_date(&date).ok()?)))
+        .collect::<HashMap<_, _>>();
+    let frozen_prices = period::frozen_prices(conn)?;
+    let components = personnel_cost::list(conn, employee_id)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT date,
+                COALESCE(SUM(hours_worked), 0) AS total_hours,
+                COUNT(CASE WHEN status = 'Present' THEN 1 END) AS work_days,
+                COUNT(CASE WHEN status = 'Krank' THEN 1 END) AS sick_days,
+                COUNT(CASE WHEN status = 'Urlaub' THEN 1 END) AS vacation_days,
+                COALESCE(SUM(kg_num), 0) AS total_kg,
+                COALESCE(SUM(mt_num), 0) AS total_mt,
+                COALESCE(SUM(mld_num), 0) AS total_mld,
+                COALESCE(SUM(mld_45_num), 0) AS total_mld_45,
+                COALESCE(SUM(mld_60_num), 0) AS total_mld_60,
+                COALESCE(SUM(ma_num), 0) AS total_ma,
+                COALESCE(SUM(fango_num), 0) AS total_fango,
+                COALESCE(SUM(ultraschal_num), 0) AS total_ultraschal,
+                COALESCE(SUM(hb_num), 0) AS total_hb
+         FROM performance
+         WHERE employee_id = ?1 AND deleted_at IS NULL
+           AND (?2 IS NULL OR date >= ?2)
+           AND (?3 IS NULL OR date <= ?3)
+           AND (?4 = 0 OR review_state = 'approved')
+         GROUP BY date
+         ORDER BY date ASC",
+    )?;
+
+    let days = stmt.query_map(params![employee_id, from, to, approved_only], |row| {
+        let date: String = row.get(0)?;
+        let work_days: i32 = row.get(2)?;
+        // One total per entry of THERAPY_COLUMNS, in the same order
+        let totals = (5..14).map(|i| row.get::<_, i32>(i)).collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let prices = date.get(..7).and_then(|month| frozen_prices.get(month)).unwrap_or(&therapy_prices);
+        let income = crate::performance::THERAPY_COLUMNS
+            .iter()
+            .zip(&totals)
+            .map(|((name, _), count)| *count as f64 * prices.get(*name).copied().unwrap_or(0.0))
+            .sum();
+        let total_hours: f64 = row.get(1)?;
+        let treatment_minutes = therapy::treatment_minutes(&totals, &durations);
+
+        Ok(MonthlyStats {
+            total_hours,
+            work_days,
+            sick_days: row.get(3)?,
+            vacation_days: row.get(4)?,
+            generated_income: income,
+            total_kg: totals[0],
+            total_mt: totals[1],
+            total_mld: totals[2],
+            total_mld_45: totals[3],
+            total_mld_60: totals[4],
+            total_ma: totals[5],
+            total_fango: totals[6],
+            total_ultraschal: totals[7],
+            total_hb: totals[8],
+            hours_needed: avg_hours * work_days as f64,
+            treatment_minutes,
+            utilization: utilization(treatment_minutes, total_hours),
+            month: date,
+            ..Default::default()
+        })
+    })?;
+
+    let mut buckets: BTreeMap<String, (Day, MonthlyStats)> = BTreeMap::new();
+    let mut recorded_months = HashSet::new();
+    for day in days {
+        let day = day?;
+        let date = granularity::parse_date(&day.month)?;
+        recorded_months.insert(Granularity::Month.bucket(date));
+        let label = granularity.bucket(date);
+        let (_, total) = buckets
+            .entry(label.clone())
+            .or_insert_with(|| (date, MonthlyStats { month: label, ..Default::default() }));
+        total.add(&day);
+    }
+
+    let from = from.map(granularity::parse_date).transpose()?;
+    let to = to.map(granularity::parse_date).transpose()?;
+    let mut stats = Vec::new();
+    for (_, (date, mut total)) in buckets {
+        let (start, end) = granularity.span(date);
+        let start = from.map_or(start, |f| f.max(start));
+        let end = to.map_or(end, |t| t.min(end));
+
+        // Days of each recorded month within the bucket since joining
+        let mut months: BTreeMap<String, i64> = BTreeMap::new();
+        for day in start..=end {
+            let month = Granularity::Month.bucket(day);
+            let joined = frozen_joined.get(&month).copied().or(joined);
+            if recorded_months.contains(&month) && joined.is_none_or(|joined| day >= joined) {
+                *months.entry(month).or_default() += 1;
+            }
+        }
+        for (month, days) in months {
+            let gross = frozen_rates.get(&month).copied().unwrap_or(emp_rate);
+            let employer = personnel_cost::employer_cost(&components, &month, gross);
+            let (month_start, month_end) = Granularity::Month.span(granularity::parse_date(&format!("{}-01", month))?);
+            let share = days as f64 / (month_end - month_start + 1) as f64;
+            let (gross, employer) = if share == 1.0 { (gross, employer) } else { (gross * share, employer * share) };
+            total.gross_salary += gross;
+            total.employer_cost += employer;
+            total.cost += gross + employer;
+        }
+        stats.push(total);
+    }
+    Ok(stats)
+}
+
+/// Per-month totals of the whole practice, summed over every employee that
+/// is not in the recycle bin. The range works like in [`monthly_stats`].
+pub fn practice_monthly_stats(
+    conn: &Connection,
+    start_month: Option<&str>,
+    end_month: Option<&str>,
+) -> Result<Vec<MonthlyStats>> {
+    let (from, to) = month_range(start_month, end_month)?;
+    practice_stats_by(conn, Granularity::Month, from.as_deref(), to.as_deref(), false)
+}
+
+/// Like [`stats_by`] for the whole practice, summed over every employee
+/// that is not in the recycle bin. Closed months keep the employees deleted
+/// since, so their totals stay as they were reported.
+pub fn practice_stats_by(
+    conn: &Connection,
+    granularity: Granularity,
+    from: Option<&str>,
+    to: Option<&str>,
+    approved_only: bool,
+) -> Result<Vec<MonthlyStats>> {
+    let mut buckets: BTreeMap<String, MonthlyStats> = BTreeMap::new();
+    let mut add = |all: Vec<MonthlyStats>| {
+        for stats in all {
+            match buckets.get_mut(&stats.month) {
+                Some(total) => total.add(&stats),
+                None => {
+                    buckets.insert(stats.month.clone(), stats);
+                }
+            }
+        }
+    };
+    for employee in employee::list(conn)? {
+        add(stats_by(conn, employee.id, granularity, from, to, approved_only)?);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT p.employee_id, c.month
+         FROM performance p
+         JOIN employees e ON e.id = p.employee_id
+         JOIN closed_periods c ON c.month = substr(p.date, 1, 7)
+         WHERE e.deleted_at IS NOT NULL AND p.deleted_at IS NULL",
+    )?;
+    let deleted = stmt
+        .query_map([], |row| Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (employee_id, month) in deleted {
+        let (start, end) = Granularity::Month.span(granularity::parse_date(&format!("{}-01", month))?);
+        let start = from.map_or(Ok(start), granularity::parse_date)?.max(start);
+        let end = to.map_or(Ok(end), granularity::parse_date)?.min(end);
+        if start <= end {
+            let (start, end) = (granularity::format_date(start), granularity::format_date(end));
+            add(stats_by(conn, employee_id, granularity, Some(&start), Some(&end), approved_only)?);
+        }
+    }
+    Ok(buckets.into_values().collect())
+}
+
+/// Treatment time per worked time.
+pub fn utilization(treatment_minutes: f64, hours_worked: f64) -> Option<f64> {
+    (hours_worked > 0.0).then(|| treatment_minutes / (hours_worked * 60.0))
+}
+
+/// Booked treatment time of one performance day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayUtilization {
+    pub id: i32,
+    pub employee_id: i32,
+    pub date: String,
+    pub hours_worked: f64,
+    pub treatment_minutes: f64,
+    pub utilization: Option<f64>,
+    /// More treatment time than worked time was entered, which usually
+    /// means a typo in the counts or the hours.
+    pub overbooked: bool,
+}
+
+/// Treatment time of every day of one employee or, without `employee_id`,
+/// of everyone. `from` and `to` are inclusive `YYYY-MM-DD` dates.
+pub fn daily_utilization(
+    conn: &Connection,
+    employee_id: Option<i32>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<DayUtilization>> {
+    let durations = therapy::durations(conn)?;
+    let days = crate::performance::list_between(conn, employee_id, from, to)?;
+    Ok(days
+        .into_iter()
+        .map(|day| {
+            let treatment_minutes = therapy::treatment_minutes(&day.therapy_counts(), &durations);
+            DayUtilization {
+                utilization: utilization(treatment_minutes, day.hours_worked),
+                overbooked: treatment_minutes > day.hours_worked * 60.0,
+                id: day.id,
+                employee_id: day.employee_id,
+                date: day.date,
+                hours_worked: day.hours_worked,
+                treatment_minutes,
+            }
+        })
+        .collect())
+}
+
+/// The days of [`daily_utilization`] with more treatment time than hours worked.
+pub fn overbooked_days(
+    conn: &Connection,
+    employee_id: Option<i32>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<DayUtilization>> {
+    let mut days = daily_utilization(conn, employee_id, from, to)?;
+    days.retain(|day| day.overbooked);
+    Ok(days)
+}
@@ -0,0 +1,112 @@
+use serde::{Serialize, Deserialize};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+use crate::audit;
+use crate::journal::Change;
+use crate::Result;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Therapy {
+    pub id: i32,
+    pub therapy_name: String,
+    pub cost: f64,
+    pub income: f64,
+    /// Length of one treatment, 0 if not known.
+    #[serde(default)]
+    pub duration_minutes: i32,
+}
+
+/// Editable fields of a therapy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TherapyInput {
+    pub therapy_name: String,
+    pub cost: f64,
+    pub income: f64,
+    #[serde(default)]
+    pub duration_minutes: i32,
+}
+
+/// All therapies that are not in the recycle bin.
+pub fn list(conn: &Connection) -> Result<Vec<Therapy>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, therapy_name, cost, income, duration_minutes
+         FROM therapy
+         WHERE deleted_at IS NULL",
+    )?;
+    let therapies = stmt.query_map([], |row| {
+        Ok(Therapy {
+            id: row.get(0)?,
+            therapy_name: row.get(1)?,
+            cost: row.get::<_, Option<f64>>(2)?.unwrap_or(0.0),
+            income: row.get::<_, Option<f64>>(3)?.unwrap_or(0.0),
+            duration_minutes: row.get(4)?,
+        })
+    })?;
+    Ok(therapies.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Current price of every therapy by name. Deleted therapies are included
+/// because their treatments still count towards historical income.
+pub fn prices(conn: &Connection) -> Result<HashMap<String, f64>> {
+    let mut stmt = conn.prepare("SELECT therapy_name, COALESCE(income, 0) FROM therapy")?;
+    let prices = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+    Ok(prices.collect::<rusqlite::Result<HashMap<_, _>>>()?)
+}
+
+/// Length of one treatment of every therapy by name, like [`prices`].
+pub fn durations(conn: &Connection) -> Result<HashMap<String, i32>> {
+    let mut stmt = conn.prepare("SELECT therapy_name, duration_minutes FROM therapy")?;
+    let durations = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?)))?;
+    Ok(durations.collect::<rusqlite::Result<HashMap<_, _>>>()?)
+}
+
+/// Booked treatment time of therapy `counts` in the order of
+/// [`crate::performance::THERAPY_COLUMNS`], with the minutes from [`durations`].
+pub fn treatment_minutes(counts: &[i32], durations: &HashMap<String, i32>) -> f64 {
+    crate::performance::THERAPY_COLUMNS
+        .iter()
+        .zip(counts)
+        .map(|((name, _), count)| (count * durations.get(*name).copied().unwrap_or(0)) as f64)
+        .fold(0.0, |total, minutes| total + minutes)
+}
+
+/// Current material cost of every therapy by name, like [`prices`].
+pub fn costs(conn: &Connection) -> Result<HashMap<String, f64>> {
+    let mut stmt = conn.prepare("SELECT therapy_name, COALESCE(cost, 0) FROM therapy")?;
+    let costs = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+    Ok(costs.collect::<rusqlite::Result<HashMap<_, _>>>()?)
+}
+
+pub fn add(conn: &Connection, therapy: &TherapyInput) -> Result<Change> {
+    audit::audited_insert(conn, "therapy", |tx| {
+        tx.execute(
+            "INSERT INTO therapy (therapy_name, cost, income, duration_minutes) VALUES (?1, ?2, ?3, ?4)",
+            params![therapy.therapy_name, therapy.cost, therapy.income, therapy.duration_minutes],
+        )?;
+        Ok(tx.last_insert_rowid())
+    })
+}
+
+pub fn update(conn: &Connection, id: i32, therapy: &TherapyInput) -> Result<Change> {
+    audit::audited(conn, "therapy", id as i64, "update", |tx| {
+        tx.execute(
+            "UPDATE therapy 
+             SET therapy_name = ?1, cost = ?2, income = ?3, duration_minutes = ?4
+             WHERE id = ?5",
+            params![therapy.therapy_name, therapy.cost, therapy.income, therapy.duration_minutes, id],
+        )?;
+        Ok(())
+    })
+}
+
+/// Moves the therapy to the recycle bin.
+pub fn delete(conn: &Connection, id: i32) -> Result<Change> {
+    audit::audited(conn, "therapy", id as i64, "delete", |tx| {
+        tx.execute(
+            "UPDATE therapy SET deleted_at = datetime('now') WHERE id = ?1 AND deleted_at IS NULL",
+            params![id],
+        )?;
+        Ok(())
+    })
+}
@@ -0,0 +1,15 @@
+//! Plain copies of the database file.
+
+use rusqlite::{params, Connection};
+
+use crate::{Error, Result};
+
+/// Writes a consistent copy of the whole database to `path`, which must
+/// not exist yet.
+pub fn backup_to(conn: &Connection, path: &std::path::Path) -> Result<()> {
+    if path.exists() {
+        return Err(Error::Invalid(format!("{} already exists", path.display())));
+    }
+    conn.execute("VACUUM INTO ?1", params![path.to_string_lossy()])?;
+    Ok(())
+}
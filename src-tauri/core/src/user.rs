@@ -0,0 +1,342 @@
+//! Local user accounts, their roles and what each role may do. The checks
+//! live here so every frontend enforces the same rules.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::comparison::Comparison;
+use crate::employee::Employee;
+use crate::journal::Change;
+use crate::kpi::KpiReport;
+use crate::stats::MonthlyStats;
+use crate::{audit, db};
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Sees and edits everything, including salaries and accounts.
+    Owner,
+    /// Runs the office: edits employees and performance, but never sees salaries.
+    OfficeManager,
+    /// Sees only their own employee record and performance.
+    Therapist,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::OfficeManager => "office_manager",
+            Role::Therapist => "therapist",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Role> {
+        [Role::Owner, Role::OfficeManager, Role::Therapist].into_iter().find(|r| r.as_str() == text)
+    }
+
+    pub fn allows(self, permission: Permission) -> bool {
+        use Permission::*;
+        match self {
+            Role::Owner => true,
+            Role::OfficeManager => matches!(
+                permission,
+                ViewAllEmployees | EditEmployees | EditPerformance | EditTherapies | ManagePeriods
+            ),
+            Role::Therapist => false,
+        }
+    }
+}
+
+/// Things a command may need beyond seeing the user's own records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Monthly rates and everything derived from them, like cost and margin.
+    ViewSalaries,
+    /// Employees and performance of everyone, not only one's own.
+    ViewAllEmployees,
+    /// Adding and deleting employees.
+    ManageEmployees,
+    /// Changing name, join date and hours of employees.
+    EditEmployees,
+    EditPerformance,
+    EditTherapies,
+    ManagePeriods,
+    ManageUsers,
+    /// Backups, restores, settings, the recycle bin and the audit log.
+    ManageData,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    pub id: i32,
+    pub username: String,
+    pub role: Role,
+    /// The employee a therapist account belongs to.
+    pub employee_id: Option<i32>,
+}
+
+impl User {
+    /// Stand-in owner while no account exists yet, so a new installation
+    /// can be set up. Recorded in the audit log under the system login.
+    pub fn setup_owner() -> User {
+        User {
+            id: 0,
+            username: audit::current_user(),
+            role: Role::Owner,
+            employee_id: None,
+        }
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        self.role.allows(permission)
+    }
+
+    pub fn ensure(&self, permission: Permission) -> Result<()> {
+        if !self.can(permission) {
+            return Err(Error::Forbidden(format!("{} may not do this", self.username)));
+        }
+        Ok(())
+    }
+
+    /// Users who can't see everyone may only access their own employee.
+    pub fn ensure_employee(&self, employee_id: i32) -> Result<()> {
+        if !self.can(Permission::ViewAllEmployees) && self.employee_id != Some(employee_id) {
+            return Err(Error::Forbidden(format!("{} may only see their own records", self.username)));
+        }
+        Ok(())
+    }
+
+    /// `employee` as JSON, without the monthly rate unless the user may see salaries.
+    pub fn employee_view(&self, employee: &Employee) -> Value {
+        let mut value = json!(employee);
+        if !self.can(Permission::ViewSalaries) {
+            value.as_object_mut().map(|o| o.remove("monthly_rate"));
+        }
+        value
+    }
+
+    /// Undoing or redoing a cost component changes salary data.
+    pub fn ensure_change(&self, change: &Change) -> Result<()> {
+        if change.entity == "cost_component" {
+            self.ensure(Permission::ViewSalaries)?;
+        }
+        Ok(())
+    }
+
+    /// `change` as JSON, without the monthly rate in the employee states and
+    /// without the states of cost components unless the user may see salaries.
+    pub fn change_view(&self, change: &Change) -> Value {
+        let mut value = json!(change);
+        if !self.can(Permission::ViewSalaries) {
+            for state in ["before", "after"] {
+                if change.entity == "cost_component" {
+                    value[state] = Value::Null;
+                } else if let Some(row) = value[state].as_object_mut() {
+                    row.remove("monthly_rate");
+                }
+            }
+        }
+        value
+    }
+
+    /// `stats` as JSON, without salary and cost unless the user may see
+    /// salaries.
+    pub fn stats_view(&self, stats: &[MonthlyStats]) -> Value {
+        let mut value = json!(stats);
+        if !self.can(Permission::ViewSalaries) {
+            for month in value.as_array_mut().into_iter().flatten() {
+                if let Some(month) = month.as_object_mut() {
+                    for field in ["gross_salary", "employer_cost", "cost"] {
+                        month.remove(field);
+                    }
+                }
+            }
+        }
+        value
+    }
+
+    /// `comparison` as JSON, without salary, cost and margin unless the user
+    /// may see salaries.
+    pub fn comparison_view(&self, comparison: &Comparison) -> Value {
+        let mut value = json!(comparison);
+        if !self.can(Permission::ViewSalaries) {
+            for period in ["current", "previous"] {
+                if let Some(stats) = value[period].as_object_mut() {
+                    for field in ["gross_salary", "employer_cost", "cost"] {
+                        stats.remove(field);
+                    }
+                }
+            }
+            value.as_object_mut().map(|o| o.remove("margin"));
+        }
+        value
+    }
+
+    /// `report` as JSON, without the figures derived from cost unless the
+    /// user may see salaries.
+    pub fn kpi_view(&self, report: &KpiReport) -> Value {
+        let mut value = json!(report);
+        if !self.can(Permission::ViewSalaries) {
+            let strip = |months: &mut Value| {
+                for month in months.as_array_mut().into_iter().flatten() {
+                    if let Some(month) = month.as_object_mut() {
+                        month.remove("cost_per_hour");
+                        month.remove("contribution_per_hour");
+                    }
+                }
+            };
+            for employee in value["employees"].as_array_mut().into_iter().flatten() {
+                strip(&mut employee["months"]);
+            }
+            strip(&mut value["practice"]);
+        }
+        value
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserInput {
+    pub username: String,
+    /// A password or PIN. Only its Argon2 hash is stored.
+    pub password: String,
+    pub role: Role,
+    pub employee_id: Option<i32>,
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<User> {
+    let role: String = row.get(2)?;
+    Ok(User {
+        id: row.get(0)?,
+        username: row.get(1)?,
+        role: Role::parse(&role).unwrap_or(Role::Therapist),
+        employee_id: row.get(3)?,
+    })
+}
+
+fn hash_password(password: &str) -> Result<String> {
+    if password.chars().count() < 4 {
+        return Err(Error::Invalid("The password or PIN needs at least 4 characters".to_string()));
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| Error::Invalid(format!("Could not hash the password: {}", e)))
+}
+
+fn validate(role: Role, employee_id: Option<i32>) -> Result<()> {
+    if role == Role::Therapist && employee_id.is_none() {
+        return Err(Error::Invalid("A therapist account must belong to an employee".to_string()));
+    }
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<User>> {
+    let mut stmt = conn.prepare("SELECT id, username, role, employee_id FROM users ORDER BY username")?;
+    let users = stmt.query_map([], from_row)?;
+    Ok(users.collect::<rusqlite::Result<_>>()?)
+}
+
+pub fn get(conn: &Connection, id: i32) -> Result<User> {
+    conn.query_row("SELECT id, username, role, employee_id FROM users WHERE id = ?1", params![id], from_row)
+        .optional()?
+        .ok_or_else(|| Error::NotFound(format!("user {}", id)))
+}
+
+pub fn count(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))?)
+}
+
+fn owner_count(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("SELECT COUNT(*) FROM users WHERE role = 'owner'", [], |row| row.get(0))?)
+}
+
+pub fn add(conn: &Connection, user: &UserInput) -> Result<User> {
+    let username = user.username.trim();
+    if username.is_empty() {
+        return Err(Error::Invalid("The user needs a name".to_string()));
+    }
+    validate(user.role, user.employee_id)?;
+    if count(conn)? == 0 && user.role != Role::Owner {
+        return Err(Error::Invalid("The first account must be an owner".to_string()));
+    }
+    let hash = hash_password(&user.password)?;
+    db::transaction(conn, |tx| {
+        tx.execute(
+            "INSERT INTO users (username, password_hash, role, employee_id, created_at) VALUES (?1, ?2, ?3, ?4, datetime('now'))",
+            params![username, hash, user.role.as_str(), user.employee_id],
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(f, _) if f.code == rusqlite::ErrorCode::ConstraintViolation => {
+                Error::Invalid(format!("The user name '{}' is taken", username))
+            }
+            e => Error::Db(e),
+        })?;
+        let created = get(tx, tx.last_insert_rowid() as i32)?;
+        audit::record(tx, "user", created.id as i64, "create", None, Some(&json!(created)))?;
+        Ok(created)
+    })
+}
+
+/// Changes the role of a user. The last owner can't be demoted.
+pub fn set_role(conn: &Connection, id: i32, role: Role, employee_id: Option<i32>) -> Result<User> {
+    validate(role, employee_id)?;
+    db::transaction(conn, |tx| {
+        let before = get(tx, id)?;
+        if before.role == Role::Owner && role != Role::Owner && owner_count(tx)? == 1 {
+            return Err(Error::Invalid("The last owner can't be given another role".to_string()));
+        }
+        tx.execute(
+            "UPDATE users SET role = ?1, employee_id = ?2 WHERE id = ?3",
+            params![role.as_str(), employee_id, id],
+        )?;
+        let after = get(tx, id)?;
+        audit::record(tx, "user", id as i64, "update", Some(&json!(before)), Some(&json!(after)))?;
+        Ok(after)
+    })
+}
+
+pub fn set_password(conn: &Connection, id: i32, password: &str) -> Result<()> {
+    let hash = hash_password(password)?;
+    db::transaction(conn, |tx| {
+        if tx.execute("UPDATE users SET password_hash = ?1 WHERE id = ?2", params![hash, id])? == 0 {
+            return Err(Error::NotFound(format!("user {}", id)));
+        }
+        audit::record(tx, "user", id as i64, "change_password", None, None)
+    })
+}
+
+/// Deletes an account. The last owner can't be deleted.
+pub fn delete(conn: &Connection, id: i32) -> Result<()> {
+    db::transaction(conn, |tx| {
+        let before = get(tx, id)?;
+        if before.role == Role::Owner && owner_count(tx)? == 1 {
+            return Err(Error::Invalid("The last owner can't be deleted".to_string()));
+        }
+        tx.execute("DELETE FROM users WHERE id = ?1", params![id])?;
+        audit::record(tx, "user", id as i64, "delete", Some(&json!(before)), None)
+    })
+}
+
+/// The user with `username` if `password` is theirs. Unknown names and
+/// wrong passwords get the same error.
+pub fn authenticate(conn: &Connection, username: &str, password: &str) -> Result<User> {
+    let wrong = || Error::Forbidden("Wrong user name or password".to_string());
+    let row = conn
+        .query_row(
+            "SELECT id, password_hash FROM users WHERE username = ?1",
+            params![username.trim()],
+            |row| Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?)),
+        )
+        .optional()?;
+    let Some((id, hash)) = row else { return Err(wrong()) };
+    let hash = PasswordHash::new(&hash).map_err(|_| wrong())?;
+    Argon2::default().verify_password(password.as_bytes(), &hash).map_err(|_| wrong())?;
+    get(conn, id)
+}
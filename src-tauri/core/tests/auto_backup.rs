@@ -0,0 +1,107 @@
+mod common;
+
+use performance_tracker_core::auto_backup::{self, AutoBackupConfig};
+use performance_tracker_core::db;
+use performance_tracker_core::repository::EmployeeRepository;
+use std::path::PathBuf;
+
+fn temp_folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("ptrack-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&folder);
+    folder
+}
+
+#[test]
+fn snapshot_and_restore() {
+    let folder = temp_folder("snapshots");
+    let (repo, anna) = common::seeded();
+    let snapshot = auto_backup::take_snapshot(repo.connection(), &folder, None).unwrap();
+    let second = auto_backup::take_snapshot(repo.connection(), &folder, None).unwrap();
+    assert_ne!(snapshot.file_name, second.file_name);
+    let listed = auto_backup::list(&folder).unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0].file_name, second.file_name);
+
+    let path = folder.join("live.sqlite");
+    let mut conn = db::open(&path).unwrap();
+    assert!(performance_tracker_core::employee::list(&conn).unwrap().is_empty());
+    auto_backup::restore(&mut conn, &snapshot.path, None).unwrap();
+    assert_eq!(performance_tracker_core::performance::list(&conn, anna).unwrap().len(), 5);
+    assert_eq!(repo.employee(anna).unwrap().name, performance_tracker_core::employee::name(&conn, anna).unwrap());
+
+    assert!(auto_backup::restore(&mut conn, &path, None).is_err());
+    // A missing snapshot fails before anything is replaced or created
+    let missing = folder.join("ptrack-2020-01-01_000000.sqlite");
+    assert!(auto_backup::open(&missing, None).is_err());
+    assert!(!missing.exists());
+    assert_eq!(performance_tracker_core::performance::list(&conn, anna).unwrap().len(), 5);
+    drop(conn);
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn rotation_keeps_daily_weekly_and_monthly_snapshots() {
+    let folder = temp_folder("rotation");
+    std::fs::create_dir_all(&folder).unwrap();
+    // Two snapshots a day over 400 days, starting 2024-01-01 (a Monday)
+    let mut day = 0;
+    for year in [2024, 2025] {
+        for month in 1..=12 {
+            for d in 1..=31 {
+                if day >= 400 || (month == 2 && d > 29) || ([4, 6, 9, 11].contains(&month) && d > 30) || (year == 2025 && month == 2 && d > 28) {
+                    continue;
+                }
+                for time in ["080000", "180000"] {
+                    let name = format!("ptrack-{}-{:02}-{:02}_{}.sqlite", year, month, d, time);
+                    std::fs::write(folder.join(name), b"").unwrap();
+                }
+                day += 1;
+            }
+        }
+    }
+    std::fs::write(folder.join("notes.txt"), b"").unwrap();
+
+    let config = AutoBackupConfig { keep_daily: 7, keep_weekly: 4, keep_monthly: 12, ..Default::default() };
+    auto_backup::rotate(&folder, &config).unwrap();
+    let kept: Vec<String> = auto_backup::list(&folder).unwrap().into_iter().map(|s| s.taken_at).collect();
+
+    // Day 400 is 2025-02-03, a Monday.
+    assert_eq!(kept[0], "2025-02-03 18:00:00");
+    assert_eq!(kept[6], "2025-01-28 18:00:00");
+    // Newest of the four most recent weeks, the current one included; the
+    // week ending 2025-02-02 is already among the daily ones
+    assert!(kept.contains(&"2025-01-26 18:00:00".to_string()));
+    assert!(kept.contains(&"2025-01-19 18:00:00".to_string()));
+    assert!(!kept.contains(&"2025-01-12 18:00:00".to_string()));
+    // Newest of each month back to March 2024
+    assert!(kept.contains(&"2024-12-31 18:00:00".to_string()));
+    assert!(kept.contains(&"2024-03-31 18:00:00".to_string()));
+    assert!(!kept.iter().any(|k| k.starts_with("2024-02")));
+    assert!(!kept.iter().any(|k| k.ends_with("08:00:00")));
+    assert_eq!(kept.len(), 7 + 2 + 10);
+    assert!(folder.join("notes.txt").exists());
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn config_round_trips_through_settings() {
+    let (repo, _) = common::seeded();
+    let conn = repo.connection();
+    assert_eq!(auto_backup::load_config(conn).unwrap(), AutoBackupConfig::default());
+
+    let config = AutoBackupConfig { folder: Some(PathBuf::from("/mnt/usb")), interval_hours: 6, keep_daily: 3, keep_weekly: 2, keep_monthly: 24 };
+    auto_backup::save_config(conn, &config).unwrap();
+    assert_eq!(auto_backup::load_config(conn).unwrap(), config);
+    assert!(auto_backup::save_config(conn, &AutoBackupConfig { interval_hours: 0, ..config }).is_err());
+}
+
+#[test]
+fn first_snapshot_is_due() {
+    let folder = temp_folder("due");
+    let (repo, _) = common::seeded();
+    let config = AutoBackupConfig::default();
+    assert!(auto_backup::is_due(&folder, &config).unwrap());
+    auto_backup::take_snapshot(repo.connection(), &folder, None).unwrap();
+    assert!(!auto_backup::is_due(&folder, &config).unwrap());
+    std::fs::remove_dir_all(&folder).unwrap();
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use performance_tracker_core::backup::{self, ConflictMode};
+use performance_tracker_core::employee::EmployeeInput;
+use performance_tracker_core::performance::PerformanceInput;
+use performance_tracker_core::repository::{EmployeeRepository, PerformanceRepository, SqliteRepository};
+use performance_tracker_core::user::{self, Role, UserInput};
+use performance_tracker_core::{migrations, period, settings, transfer, Error};
+use rusqlite::Connection;
+
+fn practice() -> (SqliteRepository, i32) {
+    let (repo, anna) = common::seeded();
+    let ben = repo.add_employee(&common::employee("Ben", 2800.0)).unwrap().entity_id as i32;
+    repo.delete_employee(ben).unwrap();
+    period::close_month(repo.connection(), "2025-01").unwrap();
+    settings::set(repo.connection(), "backup.keep", "7").unwrap();
+    (repo, anna)
+}
+
+#[test]
+fn replace_into_a_fresh_database_restores_everything() {
+    let (repo, anna) = practice();
+    let backup = backup::create(repo.connection(), false).unwrap();
+    let json = serde_json::to_string(&backup).unwrap();
+
+    let target = SqliteRepository::in_memory().unwrap();
+    let summary = backup::restore(target.connection(), &serde_json::from_str(&json).unwrap(), ConflictMode::Replace).unwrap();
+    assert_eq!((summary.restored["employees"], summary.restored["performance"]), (2, 5));
+
+    assert_eq!(backup::create(target.connection(), false).unwrap().tables, backup.tables);
+    assert_eq!(settings::get(target.connection(), "backup.keep").unwrap().as_deref(), Some("7"));
+    assert!(period::is_month_closed(target.connection(), "2025-01").unwrap());
+    assert_eq!(
+        target.monthly_stats(anna, None, None).unwrap()[0].generated_income,
+        repo.monthly_stats(anna, None, None).unwrap()[0].generated_income
+    );
+}
+
+#[test]
+fn damaged_backups_are_rejected() {
+    let (repo, _) = practice();
+    let mut backup = backup::create(repo.connection(), false).unwrap();
+    backup.tables.get_mut("employees").unwrap()[0].insert("monthly_rate".to_string(), 9999.0.into());
+
+    assert!(backup::verify(&backup).is_err());
+    let target = SqliteRepository::in_memory().unwrap();
+    assert!(backup::restore(target.connection(), &backup, ConflictMode::Replace).is_err());
+}
+
+#[test]
+fn merge_keeps_existing_rows() {
+    let (repo, anna) = practice();
+    let backup = backup::create(repo.connection(), false).unwrap();
+    repo.update_employee(anna, &common::employee("Anna Schmidt", 3400.0)).unwrap();
+    repo.add_employee(&common::employee("Carla", 3000.0)).unwrap();
+
+    let summary = backup::restore(repo.connection(), &backup, ConflictMode::Merge).unwrap();
+    assert_eq!(summary.restored["employees"], 0);
+    assert_eq!(summary.skipped["performance"], 5);
+    assert_eq!(repo.employee(anna).unwrap().name, "Anna Schmidt");
+    assert_eq!(repo.employees().unwrap().len(), 2);
+}
+
+#[test]
+fn merge_moves_employees_whose_id_is_taken() {
+    let (repo, anna) = practice();
+    let backup = backup::create(repo.connection(), false).unwrap();
+    let target = SqliteRepository::in_memory().unwrap();
+    common::seed_therapies(&target);
+    let zoe = EmployeeInput { join_date: "2023-05-01".to_string(), ..common::employee("Zoe", 2500.0) };
+    let zoe = target.add_employee(&zoe).unwrap().entity_id as i32;
+    assert_eq!(zoe, anna);
+    target.add_performance(zoe, "2025-01-02", &PerformanceInput { kg_num: 1, ..common::present(4.0) }).unwrap();
+
+    let summary = backup::restore(target.connection(), &backup, ConflictMode::Merge).unwrap();
+    let moved = summary.remapped_employees[&(anna as i64)] as i32;
+    assert_eq!(target.employee(moved).unwrap().name, "Anna");
+    assert_eq!(target.employee(zoe).unwrap().name, "Zoe");
+    assert_eq!(summary.restored["performance"], 5);
+
+    // Each keeps their own days
+    let income = |id| target.monthly_stats(id, None, None).unwrap()[0].generated_income;
+    assert_eq!((income(zoe), income(moved)), (25.0, 487.0));
+    assert_eq!(target.performances(zoe).unwrap().len(), 1);
+
+    // Merging again finds everyone
+    let again = backup::restore(target.connection(), &backup, ConflictMode::Merge).unwrap();
+    assert_eq!(again.restored["performance"], 0);
+    assert_eq!(again.remapped_employees[&(anna as i64)], moved as i64);
+    assert_eq!(target.employees().unwrap().len(), 2);
+}
+
+#[test]
+fn abort_restores_nothing_on_conflict() {
+    let (repo, _) = practice();
+    let backup = backup::create(repo.connection(), false).unwrap();
+    let target = SqliteRepository::in_memory().unwrap();
+    target.add_employee(&common::employee("Someone", 1.0)).unwrap();
+
+    let result = backup::restore(target.connection(), &backup, ConflictMode::Abort);
+    assert!(matches!(result, Err(Error::Invalid(_))));
+    assert_eq!(target.employees().unwrap().len(), 1);
+    assert!(target.performances_between(None, None, None).unwrap().is_empty());
+}
+
+#[test]
+fn backups_of_older_schemas_are_migrated() {
+    let old = Connection::open_in_memory().unwrap();
+    migrations::migrate_to(&old, 1).unwrap();
+    old.execute_batch(
+        "INSERT INTO employees (id, name, join_date, monthly_rate) VALUES (1, 'Anna', '2024-01-01', 3200);
+         INSERT INTO performance (employee_id, date, hours_worked, status, mdl_45_num) VALUES (1, '2025-01-02', 8, 'Present', 2);",
+    )
+    .unwrap();
+    let backup = backup::create(&old, false).unwrap();
+    assert_eq!(backup.schema_version, 1);
+
+    let target = SqliteRepository::in_memory().unwrap();
+    backup::restore(target.connection(), &backup, ConflictMode::Abort).unwrap();
+    assert_eq!(target.performances(1).unwrap()[0].mld_45_num, 2);
+}
+
+#[test]
+fn backups_of_newer_schemas_are_rejected() {
+    let (repo, _) = practice();
+    let mut backup = backup::create(repo.connection(), false).unwrap();
+    backup.schema_version = migrations::latest_version() + 1;
+    assert!(backup::restore(repo.connection(), &backup, ConflictMode::Replace).is_err());
+}
+
+#[test]
+fn backup_writes_a_readable_copy() {
+    let (repo, anna) = common::seeded();
+    let path = std::env::temp_dir().join(format!("ptrack-backup-test-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    transfer::backup_to(repo.connection(), &path).unwrap();
+    assert!(transfer::backup_to(repo.connection(), &path).is_err());
+
+    let copy = SqliteRepository::open(&path).unwrap();
+    assert_eq!(copy.performances(anna).unwrap().len(), 5);
+    drop(copy);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn user_accounts_are_left_out_unless_asked_for() {
+    let (repo, anna) = practice();
+    let conn = repo.connection();
+    let input = |username: &str, role, employee_id| UserInput {
+        username: username.to_string(),
+        password: "secret".to_string(),
+        role,
+        employee_id,
+    };
+    user::add(conn, &input("boss", Role::Owner, None)).unwrap();
+    user::add(conn, &input("anna", Role::Therapist, Some(anna))).unwrap();
+    assert!(!backup::create(conn, false).unwrap().tables.contains_key("users"));
+    assert_eq!(backup::create(conn, true).unwrap().tables["users"].len(), 2);
+
+    // Replacing keeps the accounts here and unlinks those whose employee is gone
+    let target = SqliteRepository::in_memory().unwrap();
+    for name in ["Xaver", "Yusuf", "Zoe"] {
+        target.add_employee(&common::employee(name, 2500.0)).unwrap();
+    }
+    user::add(target.connection(), &input("owner", Role::Owner, None)).unwrap();
+    user::add(target.connection(), &input("zoe", Role::Therapist, Some(3))).unwrap();
+    backup::restore(target.connection(), &backup::create(conn, false).unwrap(), ConflictMode::Replace).unwrap();
+    let users = user::list(target.connection()).unwrap();
+    assert_eq!(users.iter().map(|u| (u.username.as_str(), u.employee_id)).collect::<Vec<_>>(), [("owner", None), ("zoe", None)]);
+}
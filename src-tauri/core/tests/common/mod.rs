@@ -0,0 +1,77 @@
+#![allow(dead_code)]
+
+use performance_tracker_core::employee::EmployeeInput;
+use performance_tracker_core::performance::PerformanceInput;
+use performance_tracker_core::repository::{EmployeeRepository, PerformanceRepository, SqliteRepository, TherapyRepository};
+use performance_tracker_core::therapy::TherapyInput;
+
+/// Prices the golden values in the tests are computed with.
+pub const PRICES: &[(&str, f64, f64, i32)] = &[
+    // name, cost, income, minutes
+    ("kg", 2.0, 25.0, 20),
+    ("mt", 1.5, 30.0, 20),
+    ("mld-30", 1.0, 28.0, 30),
+    ("mld-45", 1.5, 40.0, 45),
+    ("mld-60", 2.0, 52.0, 60),
+    ("ma", 1.0, 18.5, 20),
+    ("fango", 4.0, 12.0, 20),
+    ("ultraschal", 0.5, 10.0, 10),
+    ("hb", 8.0, 45.0, 30),
+];
+
+pub fn employee(name: &str, monthly_rate: f64) -> EmployeeInput {
+    EmployeeInput {
+        name: name.to_string(),
+        join_date: "2024-01-01".to_string(),
+        monthly_rate,
+        avg_hours: 8.0,
+    }
+}
+
+pub fn present(hours_worked: f64) -> PerformanceInput {
+    PerformanceInput {
+        hours_worked,
+        status: "Present".to_string(),
+        ..Default::default()
+    }
+}
+
+pub fn absent(status: &str) -> PerformanceInput {
+    PerformanceInput {
+        status: status.to_string(),
+        ..Default::default()
+    }
+}
+
+pub fn seed_therapies(repo: &SqliteRepository) {
+    for (name, cost, income, duration_minutes) in PRICES {
+        repo.add_therapy(&TherapyInput {
+            therapy_name: name.to_string(),
+            cost: *cost,
+            income: *income,
+            duration_minutes: *duration_minutes,
+        })
+        .unwrap();
+    }
+}
+
+/// An in-memory repository with the therapy prices and one employee
+/// ("Anna", 3200 per month) who worked in January and February 2025.
+/// Returns the repository and Anna's id.
+pub fn seeded() -> (SqliteRepository, i32) {
+    let repo = SqliteRepository::in_memory().unwrap();
+    seed_therapies(&repo);
+    let anna = repo.add_employee(&employee("Anna", 3200.0)).unwrap().entity_id as i32;
+
+    let days = [
+        ("2025-01-02", PerformanceInput { kg_num: 6, mt_num: 2, fango_num: 3, ..present(8.0) }),
+        ("2025-01-03", PerformanceInput { kg_num: 4, mld_num: 2, mld_45_num: 1, hb_num: 1, ..present(7.5) }),
+        ("2025-01-06", absent("Krank")),
+        ("2025-02-03", PerformanceInput { mt_num: 3, mld_60_num: 2, ma_num: 4, ultraschal_num: 5, ..present(8.0) }),
+        ("2025-02-04", absent("Urlaub")),
+    ];
+    for (date, day) in &days {
+        repo.add_performance(anna, date, day).unwrap();
+    }
+    (repo, anna)
+}
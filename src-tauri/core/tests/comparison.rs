@@ -0,0 +1,101 @@
+mod common;
+
+use performance_tracker_core::comparison::{self, Period, Preset};
+use performance_tracker_core::performance::PerformanceInput;
+use performance_tracker_core::repository::PerformanceRepository;
+use performance_tracker_core::user::{Role, User};
+
+fn period(from: &str, to: &str) -> Period {
+    Period { from: from.to_string(), to: to.to_string() }
+}
+
+#[test]
+fn presets_compare_to_date() {
+    let periods = |preset: Preset, reference| preset.periods(reference).unwrap();
+    assert_eq!(
+        periods(Preset::SameMonthLastYear, "2025-03-15"),
+        (period("2025-03-01", "2025-03-15"), period("2024-03-01", "2024-03-15"))
+    );
+    assert_eq!(
+        periods(Preset::PreviousMonth, "2025-03-30"),
+        (period("2025-03-01", "2025-03-30"), period("2025-02-01", "2025-02-28"))
+    );
+    assert_eq!(
+        periods(Preset::PreviousQuarter, "2025-02-10"),
+        (period("2025-01-01", "2025-02-10"), period("2024-10-01", "2024-11-10"))
+    );
+    assert_eq!(periods(Preset::SameQuarterLastYear, "2025-08-01").1, period("2024-07-01", "2024-08-01"));
+    assert_eq!(periods(Preset::PreviousYear, "2025-06-30").1, period("2024-01-01", "2024-06-30"));
+    assert!(Preset::PreviousMonth.periods("2025-02-30").is_err());
+}
+
+#[test]
+fn presets_on_the_last_day_compare_whole_periods() {
+    let periods = |preset: Preset, reference| preset.periods(reference).unwrap();
+    assert_eq!(
+        periods(Preset::SameMonthLastYear, "2025-02-28"),
+        (period("2025-02-01", "2025-02-28"), period("2024-02-01", "2024-02-29"))
+    );
+    assert_eq!(periods(Preset::PreviousQuarter, "2025-06-30").1, period("2025-01-01", "2025-03-31"));
+    assert_eq!(periods(Preset::PreviousYear, "2024-12-31").1, period("2023-01-01", "2023-12-31"));
+}
+
+#[test]
+fn mid_month_reference_pays_for_the_same_days() {
+    let (repo, anna) = common::seeded();
+    repo.add_performance(anna, "2024-02-03", &PerformanceInput { mt_num: 3, ..common::present(8.0) }).unwrap();
+    repo.add_performance(anna, "2024-02-20", &PerformanceInput { kg_num: 8, ..common::present(8.0) }).unwrap();
+
+    let c = comparison::compare_preset(repo.connection(), Some(anna), Preset::SameMonthLastYear, "2025-02-14").unwrap();
+    assert_eq!(c.previous_period, period("2024-02-01", "2024-02-14"));
+    // Only the days up to the 14th count, and both periods pay for half a month
+    assert_eq!((c.income.current, c.income.previous), (318.0, 90.0));
+    assert_eq!((c.current.cost, c.previous.cost), (1600.0, 14.0 / 29.0 * 3200.0));
+    assert_eq!(c.hours.absolute, 0.0);
+}
+
+#[test]
+fn same_month_last_year() {
+    let (repo, anna) = common::seeded();
+    repo.add_performance(anna, "2024-01-15", &PerformanceInput { kg_num: 5, ..common::present(8.0) }).unwrap();
+    repo.add_performance(anna, "2024-01-16", &common::absent("Krank")).unwrap();
+
+    let c = comparison::compare_preset(repo.connection(), Some(anna), Preset::SameMonthLastYear, "2025-01-20").unwrap();
+    assert_eq!(c.current.month, "2025-01-01..2025-01-20");
+    assert_eq!((c.income.current, c.income.previous, c.income.absolute), (487.0, 125.0, 362.0));
+    assert!((c.income.percent.unwrap() - 289.6).abs() < 1e-9);
+    assert_eq!((c.hours.absolute, c.sick_days.percent), (7.5, Some(0.0)));
+    // A smaller loss is an improvement
+    assert_eq!(c.margin.absolute, 362.0);
+    assert!(c.margin.percent.unwrap() > 0.0);
+
+    let kg = c.therapies.iter().find(|t| t.therapy_name == "kg").unwrap();
+    assert_eq!(kg.count.percent, Some(100.0));
+    let hb = c.therapies.iter().find(|t| t.therapy_name == "hb").unwrap();
+    assert_eq!((hb.count.absolute, hb.count.percent), (1.0, None));
+}
+
+#[test]
+fn practice_and_partial_months() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let practice = comparison::compare(conn, None, &period("2025-02-01", "2025-02-28"), &period("2025-01-01", "2025-01-31")).unwrap();
+    assert_eq!(practice.income.absolute, 318.0 - 487.0);
+
+    // Half of February costs half of February's salary
+    let half = comparison::totals(conn, Some(anna), &period("2025-02-01", "2025-02-14")).unwrap();
+    assert_eq!(half.cost, 1600.0);
+    assert!(comparison::totals(conn, Some(anna), &period("2025-02-14", "2025-02-01")).is_err());
+}
+
+#[test]
+fn margin_needs_salary_access() {
+    let (repo, anna) = common::seeded();
+    let c = comparison::compare_preset(repo.connection(), Some(anna), Preset::PreviousMonth, "2025-02-01").unwrap();
+    let manager = User { id: 2, username: "office".to_string(), role: Role::OfficeManager, employee_id: None };
+    let view = manager.comparison_view(&c);
+    assert!(view.get("margin").is_none());
+    assert!(view["current"].get("cost").is_none());
+    assert!(view.get("income").is_some());
+    assert!(User::setup_owner().comparison_view(&c).get("margin").is_some());
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use performance_tracker_core::repository::{EmployeeRepository, SqliteRepository};
+use performance_tracker_core::{period, Error};
+
+#[test]
+fn add_get_and_list() {
+    let repo = SqliteRepository::in_memory().unwrap();
+    let id = repo.add_employee(&common::employee("Anna", 3200.0)).unwrap().entity_id as i32;
+    repo.add_employee(&common::employee("Ben", 2800.0)).unwrap();
+
+    let anna = repo.employee(id).unwrap();
+    assert_eq!(anna.name, "Anna");
+    assert_eq!(anna.monthly_rate, 3200.0);
+    assert_eq!(anna.avg_hours, 8.0);
+
+    let names: Vec<String> = repo.employees().unwrap().into_iter().map(|e| e.name).collect();
+    assert_eq!(names, ["Anna", "Ben"]);
+}
+
+#[test]
+fn update_changes_all_fields() {
+    let repo = SqliteRepository::in_memory().unwrap();
+    let id = repo.add_employee(&common::employee("Anna", 3200.0)).unwrap().entity_id as i32;
+
+    let mut input = common::employee("Anna Schmidt", 3400.0);
+    input.join_date = "2024-03-01".to_string();
+    input.avg_hours = 6.0;
+    let change = repo.update_employee(id, &input).unwrap();
+
+    assert_eq!(change.before.unwrap()["monthly_rate"], 3200.0);
+    assert_eq!(change.after.unwrap()["monthly_rate"], 3400.0);
+    let anna = repo.employee(id).unwrap();
+    assert_eq!(anna.name, "Anna Schmidt");
+    assert_eq!(anna.join_date, "2024-03-01");
+    assert_eq!(anna.avg_hours, 6.0);
+}
+
+#[test]
+fn delete_hides_employee_but_keeps_row() {
+    let repo = SqliteRepository::in_memory().unwrap();
+    let id = repo.add_employee(&common::employee("Anna", 3200.0)).unwrap().entity_id as i32;
+
+    repo.delete_employee(id).unwrap();
+
+    assert!(repo.employees().unwrap().is_empty());
+    assert_eq!(repo.employee(id).unwrap().name, "Anna");
+}
+
+#[test]
+fn missing_employee_is_not_found() {
+    let repo = SqliteRepository::in_memory().unwrap();
+    assert!(matches!(repo.employee(42), Err(Error::NotFound(_))));
+    assert!(matches!(repo.update_employee(42, &common::employee("X", 1.0)), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn join_date_cannot_move_into_a_closed_month() {
+    let (repo, anna) = common::seeded();
+    period::close_month(repo.connection(), "2025-01").unwrap();
+
+    let mut input = common::employee("Anna", 3200.0);
+    input.join_date = "2025-01-15".to_string();
+    assert!(matches!(repo.update_employee(anna, &input), Err(Error::PeriodClosed(_))));
+    // Moving it between open months is fine
+    input.join_date = "2023-06-01".to_string();
+    repo.update_employee(anna, &input).unwrap();
+}
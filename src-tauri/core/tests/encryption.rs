@@ -0,0 +1,82 @@
+use performance_tracker_core::{db, encryption};
+use std::path::PathBuf;
+
+fn temp_db(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ptrack-{}-{}.sqlite", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn plain_databases_are_not_encrypted() {
+    let path = temp_db("plain");
+    assert!(!encryption::is_encrypted(&path).unwrap());
+    drop(db::open(&path).unwrap());
+    assert!(!encryption::is_encrypted(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(not(feature = "encryption"))]
+#[test]
+fn builds_without_sqlcipher_refuse_to_encrypt() {
+    let path = temp_db("unsupported");
+    drop(db::open(&path).unwrap());
+    assert!(encryption::encrypt_in_place(&path, "secret").is_err());
+    assert!(!encryption::is_encrypted(&path).unwrap());
+    assert!(db::open_encrypted(&path, "secret").is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn encrypt_unlock_and_change_passphrase() {
+    use performance_tracker_core::employee::{self, EmployeeInput};
+
+    let path = temp_db("encrypted");
+    {
+        let conn = db::open(&path).unwrap();
+        employee::add(&conn, &EmployeeInput {
+            name: "Anna".to_string(),
+            join_date: "2024-01-01".to_string(),
+            monthly_rate: 3000.0,
+            avg_hours: 8.0,
+        }).unwrap();
+    }
+    encryption::encrypt_in_place(&path, "secret").unwrap();
+    assert!(encryption::is_encrypted(&path).unwrap());
+    assert!(db::open(&path).is_err());
+    assert!(db::open_encrypted(&path, "wrong").is_err());
+
+    let conn = db::open_encrypted(&path, "secret").unwrap();
+    assert_eq!(employee::list(&conn).unwrap().len(), 1);
+    encryption::change_passphrase(&conn, "new secret").unwrap();
+    drop(conn);
+
+    assert!(db::open_encrypted(&path, "secret").is_err());
+    let conn = db::open_encrypted(&path, "new secret").unwrap();
+    assert_eq!(employee::list(&conn).unwrap()[0].name, "Anna");
+    drop(conn);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn snapshots_of_encrypted_databases_stay_encrypted() {
+    use performance_tracker_core::auto_backup;
+
+    let path = temp_db("encrypted-live");
+    drop(db::open(&path).unwrap());
+    encryption::encrypt_in_place(&path, "secret").unwrap();
+    let mut conn = db::open_encrypted(&path, "secret").unwrap();
+    let folder = std::env::temp_dir().join(format!("ptrack-encrypted-snapshots-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&folder);
+
+    let snapshot = auto_backup::take_snapshot(&conn, &folder, Some("secret")).unwrap();
+    assert!(encryption::is_encrypted(&snapshot.path).unwrap());
+    assert!(auto_backup::restore(&mut conn, &snapshot.path, Some("wrong")).is_err());
+    auto_backup::restore(&mut conn, &snapshot.path, Some("secret")).unwrap();
+
+    drop(conn);
+    std::fs::remove_dir_all(&folder).unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use performance_tracker_core::export::csv::{monthly_stats_csv, performance_csv, CsvOptions};
+use performance_tracker_core::repository::{EmployeeRepository, PerformanceRepository};
+
+#[test]
+fn performance_csv_with_german_options() {
+    let (repo, anna) = common::seeded();
+    let csv = performance_csv(repo.connection(), Some(anna), Some("2025-01-01"), Some("2025-01-03"), &CsvOptions::german()).unwrap();
+
+    assert!(csv.starts_with('\u{feff}'));
+    let lines: Vec<&str> = csv.trim_start_matches('\u{feff}').split("\r\n").collect();
+    assert_eq!(
+        lines[0],
+        "employee_id;employee;date;status;hours_worked;income;kg_num;mt_num;mld_num;mld_45_num;mld_60_num;ma_num;fango_num;ultraschal_num;hb_num"
+    );
+    assert_eq!(lines[1], format!("{};Anna;2025-01-02;Present;8,00;0,00;6;2;0;0;0;0;3;0;0", anna));
+    assert_eq!(lines[2], format!("{};Anna;2025-01-03;Present;7,50;0,00;4;0;2;1;0;0;0;0;1", anna));
+    assert_eq!(lines[3], "");
+}
+
+#[test]
+fn fields_with_delimiter_are_quoted() {
+    let (repo, _) = common::seeded();
+    let ben = repo.add_employee(&common::employee("Ben; \"Jr\"", 1.0)).unwrap().entity_id as i32;
+    repo.add_performance(ben, "2025-03-01", &common::present(1.0)).unwrap();
+
+    let options = CsvOptions { bom: false, ..CsvOptions::german() };
+    let csv = performance_csv(repo.connection(), None, Some("2025-03-01"), None, &options).unwrap();
+    assert!(csv.lines().nth(1).unwrap().starts_with(&format!("{};\"Ben; \"\"Jr\"\"\";2025-03-01", ben)));
+}
+
+#[test]
+fn monthly_stats_csv_default_options() {
+    let (repo, anna) = common::seeded();
+    let stats = repo.monthly_stats(anna, None, None).unwrap();
+    let csv = monthly_stats_csv(&stats, &CsvOptions { bom: false, ..Default::default() });
+
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("month,total_hours,hours_needed,"));
+    assert_eq!(lines[1], "2025-01,15.50,16.00,2,1,0,487.00,3200.00,0.00,3200.00,-2713.00,10,2,2,1,0,0,3,0,1");
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use performance_tracker_core::export::pdf::employee_report_pdf;
+use performance_tracker_core::repository::PerformanceRepository;
+
+fn text(pdf: &[u8]) -> String {
+    String::from_utf8_lossy(pdf).into_owned()
+}
+
+#[test]
+fn report_has_table_and_chart_pages() {
+    let (repo, anna) = common::seeded();
+    let pdf = employee_report_pdf(repo.connection(), anna, None, None).unwrap();
+    let content = text(&pdf);
+
+    assert!(content.starts_with("%PDF-"));
+    assert!(content.contains("/Count 2"));
+    assert!(content.contains("(Performance report: Anna) Tj"));
+    assert!(content.contains("(2025-01) Tj"));
+    // Income with the euro sign, which is not ASCII in WinAnsi and written as hex
+    assert!(content.contains("<3438372E30302080> Tj"));
+    assert!(content.contains("(Total) Tj"));
+}
+
+#[test]
+fn long_ranges_continue_on_more_pages() {
+    let (repo, anna) = common::seeded();
+    for year in 2021..2025 {
+        for month in 1..=12 {
+            repo.add_performance(anna, &format!("{}-{:02}-10", year, month), &common::present(8.0)).unwrap();
+        }
+    }
+    let pdf = employee_report_pdf(repo.connection(), anna, None, None).unwrap();
+    assert!(text(&pdf).contains("/Count 3"));
+}
+
+#[test]
+fn unknown_employee_is_an_error() {
+    let (repo, _) = common::seeded();
+    assert!(employee_report_pdf(repo.connection(), 999, None, None).is_err());
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use std::io::{Cursor, Read};
+
+use performance_tracker_core::export::xlsx::monthly_stats_xlsx;
+use performance_tracker_core::repository::EmployeeRepository;
+
+fn part(workbook: &[u8], name: &str) -> String {
+    let mut archive = zip::ZipArchive::new(Cursor::new(workbook)).unwrap();
+    let mut content = String::new();
+    archive.by_name(name).unwrap().read_to_string(&mut content).unwrap();
+    content
+}
+
+#[test]
+fn workbook_has_summary_and_employee_sheets() {
+    let (repo, _) = common::seeded();
+    repo.add_employee(&common::employee("Ben/Jr", 2800.0)).unwrap();
+    let workbook = monthly_stats_xlsx(repo.connection(), None, None).unwrap();
+
+    let sheets = part(&workbook, "xl/workbook.xml");
+    let names: Vec<&str> = sheets.split("<sheet name=\"").skip(1).map(|s| &s[..s.find('"').unwrap()]).collect();
+    assert_eq!(names, vec!["Summary", "Anna", "BenJr"]);
+
+    // Anna: January, February and the totals row with formulas
+    let anna = part(&workbook, "xl/worksheets/sheet2.xml");
+    assert!(anna.contains("<f>SUM(B4:B5)</f>"));
+    assert!(anna.contains("<f>Q4-R4</f>"));
+    assert!(anna.contains("<v>487</v>"));
+    let summary = part(&workbook, "xl/worksheets/sheet1.xml");
+    assert!(summary.contains("<f>'Anna'!Q6</f>"));
+}
+
+#[test]
+fn empty_database_gives_a_workbook() {
+    let repo = performance_tracker_core::repository::SqliteRepository::in_memory().unwrap();
+    let workbook = monthly_stats_xlsx(repo.connection(), Some("2025-01"), Some("2025-12")).unwrap();
+    assert!(part(&workbook, "xl/worksheets/sheet1.xml").contains("<f>0</f>"));
+}
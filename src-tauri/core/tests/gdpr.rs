@@ -0,0 +1,99 @@
+mod common;
+
+use performance_tracker_core::employee::EmployeeInput;
+use performance_tracker_core::gdpr::{self, RetentionConfig};
+use performance_tracker_core::user::{self, Role, UserInput};
+use performance_tracker_core::{audit, employee, performance, period, stats, Error};
+
+fn account(username: &str, role: Role, employee_id: Option<i32>) -> UserInput {
+    UserInput {
+        username: username.to_string(),
+        password: "1234".to_string(),
+        role,
+        employee_id,
+    }
+}
+
+#[test]
+fn export_contains_everything_about_the_employee() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let bob = employee::add(conn, &common::employee("Bob", 2800.0)).unwrap().entity_id as i32;
+    let mut raise = common::employee("Anna", 3400.0);
+    raise.join_date = employee::get(conn, anna).unwrap().join_date;
+    employee::update(conn, anna, &raise).unwrap();
+    period::close_month(conn, "2025-01").unwrap();
+    user::add(conn, &account("boss", Role::Owner, None)).unwrap();
+    user::add(conn, &account("anna", Role::Therapist, Some(anna))).unwrap();
+
+    let archive = gdpr::export(conn, anna).unwrap();
+    assert_eq!(archive.format, gdpr::ARCHIVE_FORMAT);
+    assert_eq!(archive.employee["name"], "Anna");
+    assert_eq!(archive.performance.len(), 5);
+    assert_eq!(archive.user_accounts.len(), 1);
+    let rates: Vec<(f64, &str)> = archive.contract_history.iter().map(|c| (c.monthly_rate, c.source.as_str())).collect();
+    assert_eq!(rates, vec![(3200.0, "audit"), (3400.0, "audit"), (3400.0, "closed_period")]);
+    assert!(archive.audit.iter().any(|e| e.entity == "user"));
+    assert!(archive.audit.iter().all(|e| e.entity != "employee" || e.entity_id == anna as i64));
+    assert!(!archive.audit.iter().any(|e| e.entity_id == bob as i64 && e.entity == "employee"));
+
+    let json = serde_json::to_string(&archive).unwrap();
+    assert!(!json.contains("password"));
+    assert!(matches!(gdpr::export(conn, 999), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn erasure_keeps_the_totals() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    user::add(conn, &account("boss", Role::Owner, None)).unwrap();
+    user::add(conn, &account("anna", Role::Therapist, Some(anna))).unwrap();
+    let totals = || serde_json::to_value(stats::monthly_stats(conn, anna, None, None).unwrap()).unwrap();
+    let before = totals();
+
+    let summary = gdpr::erase(conn, anna, &RetentionConfig::default()).unwrap();
+    assert_eq!(summary.accounts_deleted, 1);
+    assert_eq!(summary.audit_deleted, 0);
+    assert!(summary.audit_anonymized > 0);
+
+    let erased = employee::get(conn, anna).unwrap();
+    assert_eq!(erased.name, format!("Erased employee {}", anna));
+    assert_eq!(erased.join_date, "2024-01-01");
+    assert_eq!(totals(), before);
+    assert!(user::authenticate(conn, "anna", "1234").is_err());
+
+    let log = serde_json::to_string(&audit::query(conn, None, None, None, None).unwrap()).unwrap();
+    assert!(!log.contains("\"Anna\"") && !log.contains("\"anna\""));
+    assert!(gdpr::erase(conn, anna, &RetentionConfig::default()).is_err());
+}
+
+#[test]
+fn erasure_keeps_the_cost_of_mid_month_joiners() {
+    let (repo, _) = common::seeded();
+    let conn = repo.connection();
+    let joiner = EmployeeInput { join_date: "2025-01-16".to_string(), ..common::employee("Bob", 3100.0) };
+    let bob = employee::add(conn, &joiner).unwrap().entity_id as i32;
+    performance::add(conn, bob, "2025-01-20", &common::present(8.0)).unwrap();
+    assert_eq!(stats::monthly_stats(conn, bob, None, None).unwrap()[0].cost, 1600.0);
+
+    gdpr::erase(conn, bob, &RetentionConfig::default()).unwrap();
+    assert_eq!(stats::monthly_stats(conn, bob, None, None).unwrap()[0].cost, 1600.0);
+    assert_eq!(employee::get(conn, bob).unwrap().join_date, "2025-01-16");
+}
+
+#[test]
+fn expired_audit_entries_are_deleted() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    conn.execute("UPDATE audit_log SET changed_at = datetime('now', '-3 years')", []).unwrap();
+    let config = RetentionConfig { audit_years: 2 };
+    gdpr::save_config(conn, &config).unwrap();
+    assert_eq!(gdpr::load_config(conn).unwrap(), config);
+
+    let summary = gdpr::erase(conn, anna, &config).unwrap();
+    assert!(summary.audit_deleted > 0);
+    let left = gdpr::export(conn, anna).unwrap().audit;
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].action, "erase");
+    assert_eq!(gdpr::purge_expired(conn, &config).unwrap(), 0);
+}
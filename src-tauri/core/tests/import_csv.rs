@@ -0,0 +1,105 @@
+mod common;
+
+use performance_tracker_core::import::csv::{self, MappingProfile};
+use performance_tracker_core::period;
+use performance_tracker_core::repository::PerformanceRepository;
+
+fn profile() -> MappingProfile {
+    MappingProfile {
+        name: "Praxissoftware".to_string(),
+        employee_column: "Mitarbeiter".to_string(),
+        date_column: "Datum".to_string(),
+        therapy_column: "Leistung".to_string(),
+        count_column: "Anzahl".to_string(),
+        hours_column: Some("Stunden".to_string()),
+        therapies: [("X0501", "kg"), ("X0201", "mld-30"), ("X0106", "fango")]
+            .iter()
+            .map(|(code, name)| (code.to_string(), name.to_string()))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+const FILE: &str = "\u{feff}Mitarbeiter;Datum;Leistung;Anzahl;Stunden\r\n\
+Anna;02.01.2025;X0501;3;1,5\r\n\
+anna;02.01.2025;X0501;2;1\r\n\
+Anna;10.03.2025;X0201;4;2\r\n\
+Anna;10.03.2025;HB;1;\r\n";
+
+#[test]
+fn preview_merges_rows_per_day() {
+    let (repo, anna) = common::seeded();
+    let preview = csv::preview(repo.connection(), FILE, &profile()).unwrap();
+
+    assert!(preview.errors.is_empty(), "{:?}", preview.errors);
+    assert_eq!(preview.days.len(), 2);
+    let jan = &preview.days[0];
+    assert_eq!((jan.employee_id, jan.date.as_str(), jan.hours_worked), (anna, "2025-01-02", Some(2.5)));
+    assert!(jan.performance_id.is_some());
+    assert_eq!(jan.counts["kg_num"], 5);
+    let mar = &preview.days[1];
+    assert_eq!(mar.performance_id, None);
+    assert_eq!((mar.counts["mld_num"], mar.counts["hb_num"]), (4, 1));
+}
+
+#[test]
+fn preview_reports_row_errors() {
+    let (repo, _) = common::seeded();
+    period::close_month(repo.connection(), "2025-02").unwrap();
+    let file = "Mitarbeiter;Datum;Leistung;Anzahl\n\
+Bob;02.01.2025;X0501;1\n\
+Anna;32.01.2025;X0501;1\n\
+Anna;31.02.2025;X0501;1\n\
+Anna;03.01.2025;X9999;1\n\
+Anna;03.01.2025;X0501;1,5\n\
+Anna;03.02.2025;X0501;1\n";
+    let mut p = profile();
+    p.hours_column = None;
+    let preview = csv::preview(repo.connection(), file, &p).unwrap();
+
+    let lines: Vec<usize> = preview.errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, vec![2, 3, 4, 5, 6, 7]);
+    assert!(preview.errors[5].message.starts_with(performance_tracker_core::PERIOD_CLOSED_ERROR));
+    assert!(csv::apply(repo.connection(), file, &p).is_err());
+}
+
+#[test]
+fn decode_falls_back_to_windows_1252() {
+    assert_eq!(csv::decode("Müller;€".as_bytes()), "Müller;€");
+    assert_eq!(csv::decode(b"M\xfcller;\x80 \x84Kur\x93 \x96"), "Müller;€ „Kur“ –");
+}
+
+#[test]
+fn missing_column_is_an_error() {
+    let (repo, _) = common::seeded();
+    assert!(csv::preview(repo.connection(), "Mitarbeiter;Datum\n", &profile()).is_err());
+}
+
+#[test]
+fn apply_upserts_days() {
+    let (repo, anna) = common::seeded();
+    let changes = csv::apply(repo.connection(), FILE, &profile()).unwrap();
+    assert_eq!(changes.len(), 2);
+
+    let jan = repo.performances_between(Some(anna), Some("2025-01-02"), Some("2025-01-02")).unwrap();
+    // kg is replaced, the other counts of the day are kept
+    assert_eq!((jan[0].kg_num, jan[0].mt_num, jan[0].fango_num, jan[0].hours_worked), (5, 2, 3, 2.5));
+    let mar = repo.performances_between(Some(anna), Some("2025-03-10"), None).unwrap();
+    assert_eq!(mar.len(), 1);
+    assert_eq!((mar[0].status.as_str(), mar[0].mld_num, mar[0].hb_num, mar[0].hours_worked), ("Present", 4, 1, 2.0));
+}
+
+#[test]
+fn profiles_are_saved_by_name() {
+    let (repo, _) = common::seeded();
+    let conn = repo.connection();
+    csv::save_profile(conn, &profile()).unwrap();
+    let mut changed = profile();
+    changed.delimiter = ',';
+    csv::save_profile(conn, &changed).unwrap();
+
+    assert_eq!(csv::profiles(conn).unwrap().len(), 1);
+    assert_eq!(csv::profile(conn, "Praxissoftware").unwrap().delimiter, ',');
+    csv::delete_profile(conn, "Praxissoftware").unwrap();
+    assert!(csv::profile(conn, "Praxissoftware").is_err());
+}
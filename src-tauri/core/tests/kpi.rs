@@ -0,0 +1,57 @@
+mod common;
+
+use performance_tracker_core::kpi;
+use performance_tracker_core::performance::PerformanceInput;
+use performance_tracker_core::repository::{EmployeeRepository, PerformanceRepository};
+use performance_tracker_core::user::{Role, User};
+
+fn close(a: Option<f64>, b: f64) -> bool {
+    a.is_some_and(|a| (a - b).abs() < 1e-9)
+}
+
+#[test]
+fn kpis_per_employee_and_practice() {
+    let (repo, anna) = common::seeded();
+    let ben = repo.add_employee(&common::employee("Ben", 2800.0)).unwrap().entity_id as i32;
+    repo.add_performance(ben, "2025-01-10", &PerformanceInput { kg_num: 2, ..common::present(6.0) }).unwrap();
+
+    let report = kpi::monthly_kpis(repo.connection(), None, None).unwrap();
+    let jan = &report.employees.iter().find(|e| e.employee_id == anna).unwrap().months[0];
+    assert_eq!(jan.month, "2025-01");
+    assert!(close(jan.revenue_per_hour, 487.0 / 15.5));
+    assert!(close(jan.cost_per_hour, 3200.0 / 15.5));
+    assert!(close(jan.contribution_per_hour, (487.0 - 3200.0) / 15.5));
+    // 10 kg, 2 mt, 2 mld-30, 1 mld-45, 3 fango and 1 hb
+    assert!(close(jan.treatments_per_hour, 19.0 / 15.5));
+    assert_eq!((jan.treatment_minutes, jan.paid_minutes), (435.0, 930.0));
+    assert!(close(jan.utilization, 435.0 / 930.0));
+
+    let practice = &report.practice[0];
+    assert!(close(practice.revenue_per_hour, 537.0 / 21.5));
+    assert!(close(practice.cost_per_hour, 6000.0 / 21.5));
+    assert_eq!(practice.treatment_minutes, 475.0);
+    assert_eq!(report.practice.len(), 2);
+}
+
+#[test]
+fn months_without_hours_have_no_rates() {
+    let (repo, anna) = common::seeded();
+    repo.add_performance(anna, "2025-03-03", &common::absent("Krank")).unwrap();
+    let report = kpi::monthly_kpis(repo.connection(), Some("2025-03"), Some("2025-03")).unwrap();
+    let march = &report.employees[0].months[0];
+    assert_eq!((march.revenue_per_hour, march.utilization), (None, None));
+    assert_eq!(march.paid_minutes, 0.0);
+}
+
+#[test]
+fn cost_figures_need_salary_access() {
+    let (repo, _) = common::seeded();
+    let report = kpi::monthly_kpis(repo.connection(), None, None).unwrap();
+    let manager = User { id: 2, username: "office".to_string(), role: Role::OfficeManager, employee_id: None };
+
+    let view = manager.kpi_view(&report);
+    assert!(view["employees"][0]["months"][0].get("cost_per_hour").is_none());
+    assert!(view["practice"][0].get("contribution_per_hour").is_none());
+    assert!(view["practice"][0].get("revenue_per_hour").is_some());
+    assert!(User::setup_owner().kpi_view(&report)["practice"][0].get("cost_per_hour").is_some());
+}
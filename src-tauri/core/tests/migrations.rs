@@ -0,0 +1,93 @@
+use performance_tracker_core::migrations::{self, has_column, latest_version, schema_version};
+use performance_tracker_core::{db, employee, performance};
+use rusqlite::Connection;
+
+/// The schema as the app created it before migrations existed.
+const LEGACY_SCHEMA: &str = "
+    CREATE TABLE employees (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        join_date TEXT NOT NULL,
+        monthly_rate REAL NOT NULL,
+        avg_hours REAL DEFAULT 8.0
+    );
+    CREATE TABLE performance (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        employee_id INTEGER NOT NULL,
+        date TEXT NOT NULL,
+        hours_worked REAL,
+        status TEXT,
+        income REAL,
+        kg_num INTEGER,
+        mt_num INTEGER,
+        mld_num INTEGER,
+        mdl_45_num INTEGER,
+        mld_60_num INTEGER,
+        ma_num INTEGER,
+        fango_num INTEGER,
+        ultraschal_num INTEGER,
+        hb_num INTEGER,
+        FOREIGN KEY(employee_id) REFERENCES employees(id)
+    );
+    CREATE TABLE therapy (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        therapy_name TEXT UNIQUE NOT NULL,
+        cost REAL,
+        income REAL
+    );
+    INSERT INTO employees (name, join_date, monthly_rate, avg_hours) VALUES ('Anna', '2024-01-01', 3200, 8);
+    INSERT INTO performance (employee_id, date, hours_worked, status, income, kg_num, mdl_45_num)
+        VALUES (1, '2025-01-02', 8, 'Present', 0, 6, 2);
+";
+
+#[test]
+fn fresh_database_is_at_latest_version() {
+    let conn = db::open_in_memory().unwrap();
+    assert_eq!(schema_version(&conn).unwrap(), latest_version());
+    for (table, column) in [("performance", "mld_45_num"), ("performance", "mld_60_num"), ("employees", "deleted_at")] {
+        assert!(has_column(&conn, table, column).unwrap(), "{}.{}", table, column);
+    }
+    assert!(!has_column(&conn, "performance", "mdl_45_num").unwrap());
+}
+
+#[test]
+fn legacy_database_is_upgraded_with_data() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(LEGACY_SCHEMA).unwrap();
+    assert_eq!(schema_version(&conn).unwrap(), 0);
+
+    db::init(&conn).unwrap();
+
+    assert_eq!(schema_version(&conn).unwrap(), latest_version());
+    assert!(has_column(&conn, "performance", "mld_45_num").unwrap());
+    assert!(!has_column(&conn, "performance", "mdl_45_num").unwrap());
+    assert_eq!(employee::list(&conn).unwrap()[0].name, "Anna");
+    let day = &performance::list(&conn, 1).unwrap()[0];
+    assert_eq!((day.kg_num, day.mld_45_num, day.mt_num), (6, 2, 0));
+}
+
+#[test]
+fn migrating_twice_is_a_no_op() {
+    let conn = db::open_in_memory().unwrap();
+    assert_eq!(migrations::migrate(&conn).unwrap(), latest_version());
+    db::init(&conn).unwrap();
+    assert_eq!(schema_version(&conn).unwrap(), latest_version());
+}
+
+#[test]
+fn migrate_to_stops_at_target() {
+    let conn = Connection::open_in_memory().unwrap();
+    assert_eq!(migrations::migrate_to(&conn, 2).unwrap(), 2);
+    assert!(has_column(&conn, "performance", "mld_45_num").unwrap());
+    assert!(!has_column(&conn, "employees", "deleted_at").unwrap());
+
+    assert_eq!(migrations::migrate(&conn).unwrap(), latest_version());
+    assert!(has_column(&conn, "employees", "deleted_at").unwrap());
+}
+
+#[test]
+fn newer_database_is_rejected() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&format!("PRAGMA user_version = {}", latest_version() + 1)).unwrap();
+    assert!(db::init(&conn).is_err());
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::performance::PerformanceInput;
+use performance_tracker_core::period;
+use performance_tracker_core::repository::PerformanceRepository;
+use performance_tracker_core::user::User;
+use performance_tracker_core::Error;
+
+#[test]
+fn add_and_list() {
+    let (repo, anna) = common::seeded();
+    let days = repo.performances(anna).unwrap();
+    assert_eq!(days.len(), 5);
+
+    let first = days.iter().find(|d| d.date == "2025-01-02").unwrap();
+    assert_eq!(first.status, "Present");
+    assert_eq!((first.kg_num, first.mt_num, first.fango_num), (6, 2, 3));
+    let feb = days.iter().find(|d| d.date == "2025-02-03").unwrap();
+    assert_eq!((feb.mld_60_num, feb.ma_num, feb.ultraschal_num), (2, 4, 5));
+}
+
+#[test]
+fn only_calendar_dates_are_stored() {
+    let (repo, anna) = common::seeded();
+    for date in ["2025-02-30", "2025-2-03", "03.02.2025", "2025-02"] {
+        assert!(matches!(repo.add_performance(anna, date, &common::present(8.0)), Err(Error::Invalid(_))), "{}", date);
+    }
+    assert_eq!(repo.performances(anna).unwrap().len(), 5);
+}
+
+#[test]
+fn update_and_delete() {
+    let (repo, anna) = common::seeded();
+    let day = repo.performances(anna).unwrap().into_iter().find(|d| d.date == "2025-01-06").unwrap();
+
+    let input = PerformanceInput { kg_num: 5, mld_45_num: 2, ..common::present(6.0) };
+    repo.update_performance(day.id, &input).unwrap();
+    let updated = repo.performances(anna).unwrap().into_iter().find(|d| d.id == day.id).unwrap();
+    assert_eq!(updated.status, "Present");
+    assert_eq!((updated.hours_worked, updated.kg_num, updated.mld_45_num), (6.0, 5, 2));
+
+    repo.delete_performance(day.id).unwrap();
+    assert_eq!(repo.performances(anna).unwrap().len(), 4);
+}
+
+#[test]
+fn date_range_filtering() {
+    let (repo, anna) = common::seeded();
+    let dates = |from, to| -> Vec<String> {
+        repo.performances_between(Some(anna), from, to).unwrap().into_iter().map(|d| d.date).collect()
+    };
+
+    assert_eq!(dates(Some("2025-01-03"), Some("2025-02-03")), ["2025-01-03", "2025-01-06", "2025-02-03"]);
+    assert_eq!(dates(Some("2025-02-01"), None), ["2025-02-03", "2025-02-04"]);
+    assert_eq!(dates(None, Some("2025-01-02")), ["2025-01-02"]);
+    assert_eq!(dates(None, None).len(), 5);
+    assert!(repo.performances_between(Some(anna + 1), None, None).unwrap().is_empty());
+}
+
+#[test]
+fn closed_month_rejects_edits_until_reopened() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    period::close_month(conn, "2025-01").unwrap();
+
+    let january = repo.performances(anna).unwrap().into_iter().find(|d| d.date == "2025-01-02").unwrap();
+    let result = repo.update_performance(january.id, &common::present(1.0));
+    assert!(matches!(result, Err(Error::PeriodClosed(ref month)) if month == "2025-01"));
+    assert!(matches!(repo.add_performance(anna, "2025-01-20", &common::present(8.0)), Err(Error::PeriodClosed(_))));
+    // Other months stay editable
+    repo.add_performance(anna, "2025-02-20", &common::present(8.0)).unwrap();
+
+    period::reopen_month(conn, "2025-01", Some("typo in hours")).unwrap();
+    repo.update_performance(january.id, &common::present(1.0)).unwrap();
+
+    let log = period::period_log(conn).unwrap();
+    assert_eq!(log[0].action, "reopen");
+    assert_eq!(log[0].reason.as_deref(), Some("typo in hours"));
+}
+
+#[test]
+fn undo_restores_deleted_row_with_same_id() {
+    let (repo, anna) = common::seeded();
+    let journal = Journal::default();
+    let owner = User::setup_owner();
+    let day = repo.performances(anna).unwrap().remove(0);
+
+    journal.push(&owner, repo.delete_performance(day.id).unwrap());
+    assert_eq!(repo.performances(anna).unwrap().len(), 4);
+
+    journal.undo(repo.connection(), &owner).unwrap().unwrap();
+    let restored = repo.performances(anna).unwrap().into_iter().find(|d| d.id == day.id).unwrap();
+    assert_eq!(restored.date, day.date);
+    assert_eq!(restored.kg_num, day.kg_num);
+
+    journal.redo(repo.connection(), &owner).unwrap().unwrap();
+    assert_eq!(repo.performances(anna).unwrap().len(), 4);
+    assert!(journal.redo(repo.connection(), &owner).unwrap().is_none());
+}
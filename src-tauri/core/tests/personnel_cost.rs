@@ -0,0 +1,72 @@
+mod common;
+
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::personnel_cost::{self, CostComponentInput, CostKind};
+use performance_tracker_core::user::User;
+use performance_tracker_core::{period, stats, Error};
+
+fn component(name: &str, kind: CostKind, amount: f64, valid_from: &str, valid_to: Option<&str>) -> CostComponentInput {
+    CostComponentInput {
+        name: name.to_string(),
+        kind,
+        amount,
+        valid_from: valid_from.to_string(),
+        valid_to: valid_to.map(str::to_string),
+    }
+}
+
+#[test]
+fn stats_split_salary_and_employer_cost() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    personnel_cost::add(conn, anna, &component("Social security", CostKind::Percent, 20.0, "2024-01", None)).unwrap();
+    personnel_cost::add(conn, anna, &component("Christmas bonus", CostKind::Fixed, 100.0, "2025-02", Some("2025-12"))).unwrap();
+
+    let stats = stats::monthly_stats(conn, anna, None, None).unwrap();
+    let jan = &stats[0];
+    assert_eq!((jan.gross_salary, jan.employer_cost, jan.cost), (3200.0, 640.0, 3840.0));
+    let feb = &stats[1];
+    assert_eq!((feb.gross_salary, feb.employer_cost, feb.cost), (3200.0, 740.0, 3940.0));
+
+    let practice = stats::practice_monthly_stats(conn, None, None).unwrap();
+    assert_eq!(practice[1].employer_cost, 740.0);
+}
+
+#[test]
+fn invalid_components_are_rejected() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    assert!(personnel_cost::add(conn, anna, &component(" ", CostKind::Fixed, 10.0, "2025-01", None)).is_err());
+    assert!(personnel_cost::add(conn, anna, &component("Car", CostKind::Fixed, -1.0, "2025-01", None)).is_err());
+    assert!(personnel_cost::add(conn, anna, &component("Car", CostKind::Fixed, 1.0, "2025-13", None)).is_err());
+    assert!(personnel_cost::add(conn, anna, &component("Car", CostKind::Fixed, 1.0, "2025-05", Some("2025-04"))).is_err());
+    assert!(matches!(
+        personnel_cost::add(conn, 999, &component("Car", CostKind::Fixed, 1.0, "2025-01", None)),
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[test]
+fn closed_months_keep_their_cost() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let id = personnel_cost::add(conn, anna, &component("Car", CostKind::Fixed, 300.0, "2024-06", None)).unwrap().entity_id as i32;
+    period::close_month(conn, "2025-01").unwrap();
+
+    // Ending the component after the closed month is fine, changing it isn't
+    personnel_cost::update(conn, id, &component("Car", CostKind::Fixed, 300.0, "2024-06", Some("2025-01"))).unwrap();
+    assert!(matches!(
+        personnel_cost::update(conn, id, &component("Car", CostKind::Fixed, 350.0, "2024-06", Some("2025-01"))),
+        Err(Error::PeriodClosed(_))
+    ));
+    assert!(matches!(personnel_cost::delete(conn, id), Err(Error::PeriodClosed(_))));
+    assert!(personnel_cost::add(conn, anna, &component("Bonus", CostKind::Percent, 5.0, "2025-01", None)).is_err());
+    let change = personnel_cost::add(conn, anna, &component("Bonus", CostKind::Percent, 5.0, "2025-02", None)).unwrap();
+
+    let journal = Journal::default();
+    let owner = User::setup_owner();
+    journal.push(&owner, change);
+    journal.undo(conn, &owner).unwrap();
+    assert_eq!(personnel_cost::list(conn, anna).unwrap().len(), 1);
+    assert_eq!(stats::monthly_stats(conn, anna, None, None).unwrap()[0].cost, 3500.0);
+}
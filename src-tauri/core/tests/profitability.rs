@@ -0,0 +1,52 @@
+mod common;
+
+use performance_tracker_core::performance::PerformanceInput;
+use performance_tracker_core::profitability::{self, TherapyProfit};
+use performance_tracker_core::repository::{EmployeeRepository, PerformanceRepository, TherapyRepository};
+use performance_tracker_core::therapy::TherapyInput;
+use performance_tracker_core::period;
+
+fn therapy<'a>(therapies: &'a [TherapyProfit], name: &str) -> &'a TherapyProfit {
+    therapies.iter().find(|t| t.therapy_name == name).unwrap()
+}
+
+#[test]
+fn practice_and_employee_figures() {
+    let (repo, anna) = common::seeded();
+    let ben = repo.add_employee(&common::employee("Ben", 2800.0)).unwrap().entity_id as i32;
+    repo.add_performance(ben, "2025-02-10", &PerformanceInput { fango_num: 2, kg_num: 3, ..common::present(6.0) }).unwrap();
+
+    let analysis = profitability::analyze(repo.connection(), None, None).unwrap();
+    let fango = therapy(&analysis.practice, "fango");
+    // 5 fango * 12 income, 5 * 4 cost
+    assert_eq!((fango.count, fango.revenue, fango.cost, fango.contribution_margin), (5, 60.0, 20.0, 40.0));
+    // 487 + 318 from Anna, 2 * 12 + 3 * 25 from Ben
+    assert!((fango.revenue_share - 60.0 / 904.0).abs() < 1e-9);
+    let total: f64 = analysis.practice.iter().map(|t| t.revenue_share).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+
+    let anna_figures = analysis.employees.iter().find(|e| e.employee_id == anna).unwrap();
+    let kg = therapy(&anna_figures.therapies, "kg");
+    assert_eq!((kg.count, kg.revenue, kg.cost), (10, 250.0, 20.0));
+    assert!((kg.revenue_share - 250.0 / 805.0).abs() < 1e-9);
+    let ben_figures = analysis.employees.iter().find(|e| e.employee_id == ben).unwrap();
+    assert_eq!(therapy(&ben_figures.therapies, "fango").count, 2);
+
+    let feb = profitability::analyze(repo.connection(), Some("2025-02"), Some("2025-02")).unwrap();
+    assert_eq!(therapy(&feb.practice, "fango").count, 2);
+    assert_eq!(therapy(&feb.practice, "hb").revenue_share, 0.0);
+}
+
+#[test]
+fn closed_month_keeps_the_cost() {
+    let (repo, _) = common::seeded();
+    period::close_month(repo.connection(), "2025-01").unwrap();
+    let fango = repo.therapies().unwrap().into_iter().find(|t| t.therapy_name == "fango").unwrap();
+    repo.update_therapy(fango.id, &TherapyInput { therapy_name: "fango".to_string(), cost: 10.0, income: 12.0, duration_minutes: 0 }).unwrap();
+
+    let analysis = profitability::analyze(repo.connection(), None, None).unwrap();
+    assert_eq!(therapy(&analysis.practice, "fango").cost, 12.0);
+    period::reopen_month(repo.connection(), "2025-01", None).unwrap();
+    let analysis = profitability::analyze(repo.connection(), None, None).unwrap();
+    assert_eq!(therapy(&analysis.practice, "fango").cost, 30.0);
+}
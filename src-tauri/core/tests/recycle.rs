@@ -0,0 +1,48 @@
+mod common;
+
+use performance_tracker_core::repository::{EmployeeRepository, PerformanceRepository, TherapyRepository};
+use performance_tracker_core::therapy::{self, TherapyInput};
+use performance_tracker_core::{period, recycle};
+use rusqlite::Connection;
+
+fn in_bin(conn: &Connection, entity: &str) -> usize {
+    recycle::list(conn).unwrap().iter().filter(|item| item.entity == entity).count()
+}
+
+#[test]
+fn purge_keeps_closed_months() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let bob = repo.add_employee(&common::employee("Bob", 2800.0)).unwrap().entity_id as i32;
+    repo.add_performance(bob, "2025-03-03", &common::present(8.0)).unwrap();
+    period::close_month(conn, "2025-01").unwrap();
+    repo.delete_employee(anna).unwrap();
+    repo.delete_employee(bob).unwrap();
+
+    // Bob and his day go, Anna stays in the bin with all five days
+    assert_eq!(recycle::purge(conn, 0).unwrap(), 2);
+    assert_eq!(in_bin(conn, "employee"), 1);
+    let days: i64 = conn.query_row("SELECT COUNT(*) FROM performance", [], |row| row.get(0)).unwrap();
+    assert_eq!(days, 5);
+}
+
+#[test]
+fn purge_keeps_therapies_still_counted() {
+    let (repo, _) = common::seeded();
+    let conn = repo.connection();
+    repo.add_therapy(&TherapyInput {
+        therapy_name: "sauna".to_string(),
+        cost: 1.0,
+        income: 15.0,
+        duration_minutes: 30,
+    })
+    .unwrap();
+    let id = |name: &str| therapy::list(conn).unwrap().into_iter().find(|t| t.therapy_name == name).unwrap().id;
+    let (hb, sauna) = (id("hb"), id("sauna"));
+    repo.delete_therapy(hb).unwrap();
+    repo.delete_therapy(sauna).unwrap();
+
+    assert_eq!(recycle::purge(conn, 0).unwrap(), 1);
+    assert_eq!(in_bin(conn, "therapy"), 1);
+    assert_eq!(therapy::prices(conn).unwrap()["hb"], 45.0);
+}
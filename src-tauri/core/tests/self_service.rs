@@ -0,0 +1,115 @@
+mod common;
+
+use performance_tracker_core::performance::{self, ReviewState};
+use performance_tracker_core::repository::EmployeeRepository;
+use performance_tracker_core::user::{Role, User};
+use performance_tracker_core::{review, self_service, stats, Error};
+
+fn therapist(employee_id: i32) -> User {
+    User {
+        id: 2,
+        username: "anna".to_string(),
+        role: Role::Therapist,
+        employee_id: Some(employee_id),
+    }
+}
+
+#[test]
+fn therapists_see_their_own_days_without_cost() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let user = therapist(anna);
+    assert_eq!(self_service::own_performance(conn, &user).unwrap().len(), 5);
+
+    let own = self_service::own_monthly_stats(conn, &user, None, None).unwrap();
+    let full = stats::monthly_stats(conn, anna, None, None).unwrap();
+    assert_eq!(own.len(), full.len());
+    assert_eq!(own[0].generated_income, full[0].generated_income);
+    assert!(serde_json::to_value(&own).unwrap()[0].get("cost").is_none());
+
+    let unlinked = User { employee_id: None, ..user };
+    assert!(matches!(self_service::own_performance(conn, &unlinked), Err(Error::Forbidden(_))));
+}
+
+#[test]
+fn days_go_through_the_review() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let user = therapist(anna);
+    assert!(performance::list(conn, anna).unwrap().iter().all(|p| p.review_state == ReviewState::Approved));
+
+    // Monday and Wednesday of one week, and the Monday after
+    let day = |date| self_service::enter_day(conn, &user, date, &common::present(6.0)).unwrap().entity_id as i32;
+    let (monday, wednesday, next_week) = (day("2025-03-03"), day("2025-03-05"), day("2025-03-10"));
+    assert_eq!(performance::get(conn, monday).unwrap().review_state, ReviewState::Draft);
+    assert!(review::approve(conn, &[monday]).is_err());
+
+    assert_eq!(self_service::submit_week(conn, &user, "2025-03-09").unwrap().len(), 2);
+    assert_eq!(review::pending(conn, None).unwrap().len(), 2);
+    assert_eq!(performance::get(conn, next_week).unwrap().review_state, ReviewState::Draft);
+
+    assert!(review::reject(conn, &[wednesday], " ").is_err());
+    review::reject(conn, &[wednesday], "Fango count is missing").unwrap();
+    review::approve(conn, &[monday]).unwrap();
+    assert!(review::approve(conn, &[monday, wednesday]).is_err());
+    assert_eq!(performance::get(conn, monday).unwrap().review_state, ReviewState::Approved);
+
+    let rejected = performance::get(conn, wednesday).unwrap();
+    assert_eq!(rejected.review_state, ReviewState::Rejected);
+    assert_eq!(rejected.review_comment.as_deref(), Some("Fango count is missing"));
+    self_service::update_day(conn, &user, wednesday, &common::present(7.0)).unwrap();
+    assert_eq!(self_service::submit_month(conn, &user, "2025-03").unwrap().len(), 2);
+    review::approve(conn, &[wednesday, next_week]).unwrap();
+    assert_eq!(performance::get(conn, wednesday).unwrap().review_comment, None);
+
+    // Approved and submitted days stay as they are until the office reopens them
+    assert!(self_service::delete_day(conn, &user, monday).is_err());
+    assert!(matches!(self_service::update_day(conn, &user, monday, &common::present(5.0)), Err(Error::Invalid(_))));
+    assert_eq!(performance::get(conn, monday).unwrap().hours_worked, 6.0);
+    review::reopen(conn, &[next_week, wednesday]).unwrap();
+    assert!(review::reopen(conn, &[monday, next_week]).is_err());
+    review::reopen(conn, &[monday]).unwrap();
+    self_service::update_day(conn, &user, monday, &common::present(5.0)).unwrap();
+    self_service::delete_day(conn, &user, monday).unwrap();
+}
+
+#[test]
+fn stats_can_leave_out_days_in_review() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    self_service::enter_day(conn, &therapist(anna), "2025-02-05", &common::present(8.0)).unwrap();
+    let all = stats::monthly_stats(conn, anna, None, None).unwrap();
+    let approved = stats::monthly_stats_filtered(conn, anna, None, None, true).unwrap();
+    assert_eq!(all[1].total_hours, approved[1].total_hours + 8.0);
+    assert_eq!(all[0].total_hours, approved[0].total_hours);
+}
+
+#[test]
+fn therapists_cannot_touch_other_days() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let bob = repo.add_employee(&common::employee("Bob", 2800.0)).unwrap().entity_id as i32;
+    let id = performance::add(conn, bob, "2025-03-03", &common::present(8.0)).unwrap().entity_id as i32;
+
+    let user = therapist(anna);
+    assert!(matches!(self_service::update_day(conn, &user, id, &common::present(1.0)), Err(Error::Forbidden(_))));
+    assert!(matches!(self_service::delete_day(conn, &user, id), Err(Error::Forbidden(_))));
+}
+
+#[test]
+fn submitting_needs_a_real_date_or_month() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let user = therapist(anna);
+    self_service::enter_day(conn, &user, "2025-02-28", &common::present(6.0)).unwrap();
+
+    for date in ["2025-02-30", "2025-13-01", "28.02.2025", ""] {
+        assert!(matches!(self_service::submit_week(conn, &user, date), Err(Error::Invalid(_))), "{}", date);
+    }
+    for month in ["2025-13", "2025-2", "2025-02-01", "Feb"] {
+        assert!(matches!(self_service::submit_month(conn, &user, month), Err(Error::Invalid(_))), "{}", month);
+    }
+    assert_eq!(review::pending(conn, None).unwrap().len(), 0);
+    // The week of Friday the 28th runs into March
+    assert_eq!(self_service::submit_week(conn, &user, "2025-03-02").unwrap().len(), 1);
+}
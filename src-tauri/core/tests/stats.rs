@@ -0,0 +1,218 @@
+//! Golden values for the monthly stats. If one of these changes, income
+//! totals that have already been reported would change too.
+
+mod common;
+
+use performance_tracker_core::employee::EmployeeInput;
+use performance_tracker_core::performance::PerformanceInput;
+use performance_tracker_core::granularity::{self, Granularity};
+use performance_tracker_core::{period, stats};
+use performance_tracker_core::repository::{EmployeeRepository, PerformanceRepository, TherapyRepository};
+use performance_tracker_core::therapy::TherapyInput;
+
+#[test]
+fn golden_monthly_stats() {
+    let (repo, anna) = common::seeded();
+    let stats = repo.monthly_stats(anna, None, None).unwrap();
+    assert_eq!(stats.len(), 2);
+
+    let jan = &stats[0];
+    assert_eq!(jan.month, "2025-01");
+    assert_eq!(jan.total_hours, 15.5);
+    assert_eq!((jan.work_days, jan.sick_days, jan.vacation_days), (2, 1, 0));
+    assert_eq!(jan.cost, 3200.0);
+    // 10 kg * 25 + 2 mt * 30 + 2 mld-30 * 28 + 1 mld-45 * 40 + 3 fango * 12 + 1 hb * 45
+    assert_eq!(jan.generated_income, 487.0);
+    assert_eq!((jan.total_kg, jan.total_mt, jan.total_mld, jan.total_mld_45), (10, 2, 2, 1));
+    assert_eq!((jan.total_mld_60, jan.total_ma, jan.total_fango), (0, 0, 3));
+    assert_eq!((jan.total_ultraschal, jan.total_hb), (0, 1));
+    assert_eq!(jan.hours_needed, 16.0);
+
+    let feb = &stats[1];
+    assert_eq!(feb.month, "2025-02");
+    assert_eq!(feb.total_hours, 8.0);
+    assert_eq!((feb.work_days, feb.sick_days, feb.vacation_days), (1, 0, 1));
+    // 3 mt * 30 + 2 mld-60 * 52 + 4 ma * 18.5 + 5 ultraschal * 10
+    assert_eq!(feb.generated_income, 318.0);
+    assert_eq!(feb.hours_needed, 8.0);
+}
+
+#[test]
+fn month_range_is_inclusive() {
+    let (repo, anna) = common::seeded();
+    let months = |start, end| -> Vec<String> {
+        repo.monthly_stats(anna, start, end).unwrap().into_iter().map(|s| s.month).collect()
+    };
+
+    assert_eq!(months(Some("2025-02"), Some("2025-02")), ["2025-02"]);
+    assert_eq!(months(Some("2024-12"), Some("2025-01")), ["2025-01"]);
+    assert!(months(Some("2025-03"), Some("2025-12")).is_empty());
+    // A single bound is ignored, like before
+    assert_eq!(months(Some("2025-02"), None).len(), 2);
+}
+
+#[test]
+fn deleted_days_do_not_count() {
+    let (repo, anna) = common::seeded();
+    let day = repo.performances(anna).unwrap().into_iter().find(|d| d.date == "2025-02-03").unwrap();
+    repo.delete_performance(day.id).unwrap();
+
+    let feb = repo.monthly_stats(anna, Some("2025-02"), Some("2025-02")).unwrap();
+    assert_eq!(feb[0].generated_income, 0.0);
+    assert_eq!(feb[0].work_days, 0);
+}
+
+#[test]
+fn closed_month_keeps_rate_and_prices() {
+    let (repo, anna) = common::seeded();
+    period::close_month(repo.connection(), "2025-01").unwrap();
+
+    let kg = repo.therapies().unwrap().into_iter().find(|t| t.therapy_name == "kg").unwrap();
+    repo.update_therapy(kg.id, &TherapyInput { therapy_name: "kg".to_string(), cost: 2.0, income: 30.0, duration_minutes: 0 }).unwrap();
+    repo.update_employee(anna, &common::employee("Anna", 3500.0)).unwrap();
+
+    let stats = repo.monthly_stats(anna, None, None).unwrap();
+    assert_eq!(stats[0].generated_income, 487.0);
+    assert_eq!(stats[0].cost, 3200.0);
+    // The open month uses the new values
+    assert_eq!(stats[1].cost, 3500.0);
+
+    period::reopen_month(repo.connection(), "2025-01", None).unwrap();
+    let jan = &repo.monthly_stats(anna, Some("2025-01"), Some("2025-01")).unwrap()[0];
+    assert_eq!(jan.generated_income, 487.0 + 10.0 * 5.0);
+    assert_eq!(jan.cost, 3500.0);
+}
+
+#[test]
+fn closed_month_keeps_the_join_date() {
+    let (repo, _) = common::seeded();
+    let conn = repo.connection();
+    let joiner = EmployeeInput { join_date: "2025-01-16".to_string(), ..common::employee("Bob", 3100.0) };
+    let bob = repo.add_employee(&joiner).unwrap().entity_id as i32;
+    repo.add_performance(bob, "2025-01-20", &common::present(8.0)).unwrap();
+    assert_eq!(repo.monthly_stats(bob, None, None).unwrap()[0].cost, 1600.0);
+
+    // However the join date changes later, e.g. by undo or a restore
+    period::close_month(conn, "2025-01").unwrap();
+    conn.execute("UPDATE employees SET join_date = '2025-01-01' WHERE id = ?1", [bob]).unwrap();
+    assert_eq!(repo.monthly_stats(bob, None, None).unwrap()[0].cost, 1600.0);
+
+    period::reopen_month(conn, "2025-01", None).unwrap();
+    assert_eq!(repo.monthly_stats(bob, None, None).unwrap()[0].cost, 3100.0);
+}
+
+#[test]
+fn practice_stats_sum_all_employees() {
+    let (repo, anna) = common::seeded();
+    let ben = repo.add_employee(&common::employee("Ben", 2800.0)).unwrap().entity_id as i32;
+    repo.add_performance(ben, "2025-01-10", &PerformanceInput { kg_num: 2, ..common::present(6.0) }).unwrap();
+    // Deleted employees are left out
+    let gone = repo.add_employee(&common::employee("Gone", 1000.0)).unwrap().entity_id as i32;
+    repo.add_performance(gone, "2025-01-10", &PerformanceInput { kg_num: 9, ..common::present(8.0) }).unwrap();
+    repo.delete_employee(gone).unwrap();
+
+    let practice = stats::practice_monthly_stats(repo.connection(), None, None).unwrap();
+    assert_eq!(practice.len(), 2);
+    assert_eq!(practice[0].month, "2025-01");
+    assert_eq!(practice[0].generated_income, 487.0 + 50.0);
+    assert_eq!(practice[0].cost, 3200.0 + 2800.0);
+    assert_eq!(practice[0].total_hours, 21.5);
+    assert_eq!(practice[0].work_days, 3);
+    // February only has Anna
+    assert_eq!(practice[1].cost, repo.monthly_stats(anna, None, None).unwrap()[1].cost);
+}
+
+#[test]
+fn closed_months_keep_deleted_employees() {
+    let (repo, _) = common::seeded();
+    let conn = repo.connection();
+    let ben = repo.add_employee(&common::employee("Ben", 2800.0)).unwrap().entity_id as i32;
+    repo.add_performance(ben, "2025-01-10", &PerformanceInput { kg_num: 2, ..common::present(6.0) }).unwrap();
+    repo.add_performance(ben, "2025-02-10", &PerformanceInput { kg_num: 2, ..common::present(6.0) }).unwrap();
+    period::close_month(conn, "2025-01").unwrap();
+    let before = stats::practice_monthly_stats(conn, None, None).unwrap();
+
+    repo.delete_employee(ben).unwrap();
+    let after = stats::practice_monthly_stats(conn, None, None).unwrap();
+    assert_eq!(after[0].generated_income, before[0].generated_income);
+    assert_eq!(after[0].cost, 3200.0 + 2800.0);
+    // The open month drops Ben
+    assert_eq!(after[1].generated_income, before[1].generated_income - 50.0);
+}
+
+#[test]
+fn treatment_time_and_overbooked_days() {
+    let (repo, anna) = common::seeded();
+    // 30 kg of 20 minutes in 8 hours
+    repo.add_performance(anna, "2025-02-05", &PerformanceInput { kg_num: 30, ..common::present(8.0) }).unwrap();
+
+    let jan = &repo.monthly_stats(anna, Some("2025-01"), Some("2025-01")).unwrap()[0];
+    // 10 kg * 20 + 2 mt * 20 + 2 mld-30 * 30 + 1 mld-45 * 45 + 3 fango * 20 + 1 hb * 30
+    assert_eq!(jan.treatment_minutes, 435.0);
+    assert_eq!(jan.utilization, Some(435.0 / 930.0));
+
+    let days = stats::daily_utilization(repo.connection(), Some(anna), None, None).unwrap();
+    assert_eq!(days.len(), 6);
+    assert_eq!((days[0].date.as_str(), days[0].treatment_minutes), ("2025-01-02", 220.0));
+    assert_eq!(days[2].utilization, None);
+
+    let overbooked = stats::overbooked_days(repo.connection(), None, None, None).unwrap();
+    assert_eq!(overbooked.len(), 1);
+    assert_eq!((overbooked[0].date.as_str(), overbooked[0].treatment_minutes), ("2025-02-05", 600.0));
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+#[test]
+fn stats_by_week_quarter_and_year() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+
+    let weeks = stats::stats_by(conn, anna, Granularity::Week, None, None, false).unwrap();
+    let labels: Vec<&str> = weeks.iter().map(|w| w.month.as_str()).collect();
+    assert_eq!(labels, ["2025-W01", "2025-W02", "2025-W06"]);
+    // 2024-12-30 to 2025-01-05: December has no days, so five days of January
+    assert!(close(weeks[0].cost, 5.0 / 31.0 * 3200.0));
+    assert_eq!(weeks[0].total_hours, 15.5);
+    assert!(close(weeks[2].cost, 7.0 / 28.0 * 3200.0));
+
+    let quarters = stats::stats_by(conn, anna, Granularity::Quarter, None, None, false).unwrap();
+    assert_eq!(quarters.len(), 1);
+    assert_eq!(quarters[0].month, "2025-Q1");
+    assert_eq!(quarters[0].generated_income, 487.0 + 318.0);
+    // March has no days and costs nothing, like it has no monthly bucket
+    assert_eq!(quarters[0].cost, 6400.0);
+    assert_eq!((quarters[0].work_days, quarters[0].sick_days, quarters[0].vacation_days), (3, 1, 1));
+
+    // Only the months within the range are paid for
+    let year = stats::stats_by(conn, anna, Granularity::Year, Some("2025-01-01"), Some("2025-02-28"), false).unwrap();
+    assert_eq!((year[0].month.as_str(), year[0].cost), ("2025", 6400.0));
+    assert_eq!(stats::stats_by(conn, anna, Granularity::Year, None, None, false).unwrap()[0].cost, 6400.0);
+
+    let days = stats::stats_by(conn, anna, Granularity::Day, None, None, false).unwrap();
+    assert_eq!(days.len(), 5);
+    assert!(close(days[0].cost, 3200.0 / 31.0));
+
+    let practice = stats::practice_stats_by(conn, Granularity::Quarter, None, None, false).unwrap();
+    assert_eq!(practice[0].cost, 6400.0);
+
+    // Nothing is paid before the join date
+    let bob = repo.add_employee(&EmployeeInput { join_date: "2025-02-15".to_string(), ..common::employee("Bob", 2800.0) }).unwrap().entity_id as i32;
+    repo.add_performance(bob, "2025-02-17", &common::present(8.0)).unwrap();
+    let quarters = stats::stats_by(conn, bob, Granularity::Quarter, None, None, false).unwrap();
+    assert_eq!(quarters[0].cost, 14.0 / 28.0 * 2800.0);
+}
+
+#[test]
+fn calendar_buckets() {
+    let day = |date| granularity::parse_date(date).unwrap();
+    assert_eq!(Granularity::Week.bucket(day("2021-01-01")), "2020-W53");
+    assert_eq!(Granularity::Week.bucket(day("2024-12-30")), "2025-W01");
+    assert_eq!(Granularity::Quarter.bucket(day("2024-12-31")), "2024-Q4");
+    assert_eq!(Granularity::Quarter.span(day("2024-02-10")), (day("2024-01-01"), day("2024-03-31")));
+    assert_eq!(granularity::format_date(day("2024-02-29")), "2024-02-29");
+    assert!(granularity::parse_date("2025-02-29").is_err());
+    assert!(granularity::parse_date("2025-1-01").is_err());
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use performance_tracker_core::repository::{SqliteRepository, TherapyRepository};
+use performance_tracker_core::therapy::{self, TherapyInput};
+
+#[test]
+fn add_update_and_delete() {
+    let repo = SqliteRepository::in_memory().unwrap();
+    common::seed_therapies(&repo);
+    assert_eq!(repo.therapies().unwrap().len(), common::PRICES.len());
+
+    let kg = repo.therapies().unwrap().into_iter().find(|t| t.therapy_name == "kg").unwrap();
+    repo.update_therapy(kg.id, &TherapyInput { therapy_name: "kg".to_string(), cost: 2.5, income: 26.0, duration_minutes: 0 })
+        .unwrap();
+    let kg = repo.therapies().unwrap().into_iter().find(|t| t.therapy_name == "kg").unwrap();
+    assert_eq!((kg.cost, kg.income), (2.5, 26.0));
+
+    repo.delete_therapy(kg.id).unwrap();
+    assert!(repo.therapies().unwrap().iter().all(|t| t.therapy_name != "kg"));
+    // Deleted therapies keep their price for historical income
+    assert_eq!(therapy::prices(repo.connection()).unwrap()["kg"], 26.0);
+}
+
+#[test]
+fn therapy_names_are_unique() {
+    let repo = SqliteRepository::in_memory().unwrap();
+    let input = TherapyInput { therapy_name: "kg".to_string(), cost: 1.0, income: 2.0, duration_minutes: 0 };
+    repo.add_therapy(&input).unwrap();
+    assert!(repo.add_therapy(&input).is_err());
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use performance_tracker_core::journal::{Change, Journal};
+use performance_tracker_core::repository::EmployeeRepository;
+use performance_tracker_core::user::{self, Permission, Role, User, UserInput};
+use performance_tracker_core::{audit, employee, performance, self_service, stats, Error};
+
+fn input(username: &str, role: Role, employee_id: Option<i32>) -> UserInput {
+    UserInput {
+        username: username.to_string(),
+        password: "1234".to_string(),
+        role,
+        employee_id,
+    }
+}
+
+#[test]
+fn first_account_must_be_an_owner() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    assert!(user::add(conn, &input("anna", Role::Therapist, Some(anna))).is_err());
+    user::add(conn, &input("boss", Role::Owner, None)).unwrap();
+    user::add(conn, &input("anna", Role::Therapist, Some(anna))).unwrap();
+    assert!(user::add(conn, &input("anna", Role::Therapist, Some(anna))).is_err());
+    assert!(user::add(conn, &input("bob", Role::Therapist, None)).is_err());
+    assert_eq!(user::count(conn).unwrap(), 2);
+}
+
+#[test]
+fn authenticate_checks_the_password() {
+    let (repo, _) = common::seeded();
+    let conn = repo.connection();
+    let boss = user::add(conn, &input("boss", Role::Owner, None)).unwrap();
+    assert_eq!(user::authenticate(conn, "boss", "1234").unwrap(), boss);
+    assert!(matches!(user::authenticate(conn, "boss", "4321"), Err(Error::Forbidden(_))));
+    assert!(matches!(user::authenticate(conn, "nobody", "1234"), Err(Error::Forbidden(_))));
+
+    user::set_password(conn, boss.id, "new password").unwrap();
+    assert!(user::authenticate(conn, "boss", "1234").is_err());
+    user::authenticate(conn, "boss", "new password").unwrap();
+    assert!(user::set_password(conn, boss.id, "12").is_err());
+}
+
+#[test]
+fn last_owner_stays() {
+    let (repo, _) = common::seeded();
+    let conn = repo.connection();
+    let boss = user::add(conn, &input("boss", Role::Owner, None)).unwrap();
+    assert!(user::delete(conn, boss.id).is_err());
+    assert!(user::set_role(conn, boss.id, Role::OfficeManager, None).is_err());
+
+    let second = user::add(conn, &input("second", Role::Owner, None)).unwrap();
+    user::set_role(conn, boss.id, Role::OfficeManager, None).unwrap();
+    assert!(user::delete(conn, second.id).is_err());
+    user::delete(conn, boss.id).unwrap();
+}
+
+#[test]
+fn roles_limit_permissions_and_salaries() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    user::add(conn, &input("boss", Role::Owner, None)).unwrap();
+    let office = user::add(conn, &input("office", Role::OfficeManager, None)).unwrap();
+    let therapist = user::add(conn, &input("anna", Role::Therapist, Some(anna))).unwrap();
+
+    assert!(office.can(Permission::EditPerformance));
+    assert!(!office.can(Permission::ViewSalaries));
+    assert!(office.ensure(Permission::ManageUsers).is_err());
+    assert!(office.ensure_employee(anna).is_ok());
+    assert!(therapist.ensure(Permission::EditPerformance).is_err());
+    assert!(therapist.ensure_employee(anna).is_ok());
+    assert!(therapist.ensure_employee(anna + 1).is_err());
+
+    let employee = employee::get(conn, anna).unwrap();
+    assert_eq!(User::setup_owner().employee_view(&employee)["monthly_rate"], 3200.0);
+    assert!(office.employee_view(&employee).get("monthly_rate").is_none());
+    assert_eq!(therapist.employee_view(&employee)["name"], "Anna");
+
+    let months = stats::monthly_stats(conn, anna, None, None).unwrap();
+    let view = therapist.stats_view(&months);
+    assert_eq!(view.as_array().unwrap().len(), months.len());
+    assert!(view[0].get("cost").is_none());
+    assert!(view[0].get("generated_income").is_some());
+}
+
+#[test]
+fn changes_are_recorded_under_the_actor() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    audit::set_actor(conn, "office").unwrap();
+    repo.update_employee(anna, &common::employee("Anna B.", 3200.0)).unwrap();
+    let entry = &audit::query(conn, Some("employee"), Some(anna as i64), None, None).unwrap()[0];
+    assert_eq!(entry.action, "update");
+    assert_eq!(entry.changed_by, "office");
+}
+
+#[test]
+fn undone_changes_hide_salaries() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let office = User { id: 2, username: "office".to_string(), role: Role::OfficeManager, employee_id: None };
+    let journal = Journal::default();
+    journal.push(&User::setup_owner(), employee::update(conn, anna, &common::employee("Anna B.", 3400.0)).unwrap());
+
+    let change = journal.undo(conn, &User::setup_owner()).unwrap().unwrap();
+    let view = office.change_view(&change);
+    assert_eq!(view["before"]["name"], "Anna");
+    assert!(view["before"].get("monthly_rate").is_none());
+    assert!(view["after"].get("monthly_rate").is_none());
+    assert_eq!(User::setup_owner().change_view(&change)["after"]["monthly_rate"], 3400.0);
+
+    let component = Change::new("cost_component", 1, None, Some(serde_json::json!({ "amount": 20.0 })));
+    assert!(office.ensure_change(&component).is_err());
+    assert!(office.change_view(&component)["after"].is_null());
+    assert!(office.ensure_change(&change).is_ok());
+}
+
+#[test]
+fn users_undo_their_own_changes() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let therapist = User { id: 3, username: "anna".to_string(), role: Role::Therapist, employee_id: Some(anna) };
+    let office = User { id: 2, username: "office".to_string(), role: Role::OfficeManager, employee_id: None };
+    let journal = Journal::default();
+    journal.push(&therapist, self_service::enter_day(conn, &therapist, "2025-03-03", &common::present(8.0)).unwrap());
+    journal.push(&office, employee::update(conn, anna, &common::employee("Anna B.", 3200.0)).unwrap());
+
+    // The therapist skips the office's change and undoes their own day
+    let change = journal.undo(conn, &therapist).unwrap().unwrap();
+    assert_eq!(change.after.unwrap()["date"], "2025-03-03");
+    assert!(journal.undo(conn, &therapist).unwrap().is_none());
+    assert_eq!(employee::name(conn, anna).unwrap(), "Anna B.");
+    // The office may undo everyone's changes
+    journal.undo(conn, &office).unwrap().unwrap();
+    assert_eq!(employee::name(conn, anna).unwrap(), "Anna");
+    journal.redo(conn, &therapist).unwrap().unwrap();
+    assert_eq!(self_service::own_performance(conn, &therapist).unwrap().len(), 6);
+}
+
+#[test]
+fn own_changes_overwritten_by_others_stay() {
+    let (repo, anna) = common::seeded();
+    let conn = repo.connection();
+    let therapist = User { id: 3, username: "anna".to_string(), role: Role::Therapist, employee_id: Some(anna) };
+    let journal = Journal::default();
+    let entered = self_service::enter_day(conn, &therapist, "2025-03-03", &common::present(8.0)).unwrap();
+    let id = entered.entity_id as i32;
+    journal.push(&therapist, entered);
+    journal.push(&User::setup_owner(), performance::update(conn, id, &common::present(6.0)).unwrap());
+
+    assert!(matches!(journal.undo(conn, &therapist), Err(Error::Invalid(_))));
+    assert_eq!(self_service::own_performance(conn, &therapist).unwrap().len(), 6);
+}
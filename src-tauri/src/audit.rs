@@ -0,0 +1,22 @@
+use performance_tracker_core::audit::{self, AuditEntry};
+use performance_tracker_core::user::Permission;
+use tauri::AppHandle;
+
+use crate::{db, user};
+
+/// Lists audit entries, newest first. `from` and `to` are inclusive
+/// `YYYY-MM-DD` dates.
+#[tauri::command]
+pub fn get_audit_log(
+    app_handle: AppHandle,
+    entity: Option<String>,
+    entity_id: Option<i64>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<AuditEntry>, String> {
+    println!("🔍 get_audit_log command called: {:?} {:?} {:?} - {:?}", entity, entity_id, from, to);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    audit::query(&conn, entity.as_deref(), entity_id, from.as_deref(), to.as_deref())
+        .map_err(|e| e.to_string())
+}
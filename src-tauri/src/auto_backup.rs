@@ -0,0 +1,115 @@
+use performance_tracker_core::auto_backup::{self, AutoBackupConfig, SnapshotInfo};
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::user::Permission;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{db, user};
+
+/// How often the background task checks whether a snapshot is due.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+fn backup_folder(app_handle: &AppHandle, config: &AutoBackupConfig) -> PathBuf {
+    config.folder.clone().unwrap_or_else(|| {
+        app_handle
+            .path()
+            .app_data_dir()
+            .expect("Failed to get app data directory")
+            .join("backups")
+    })
+}
+
+/// Takes a snapshot without rotating, so no existing snapshot goes away.
+fn snapshot_only(app_handle: &AppHandle) -> performance_tracker_core::Result<SnapshotInfo> {
+    let conn = db::init_db(app_handle)?;
+    let config = auto_backup::load_config(&conn)?;
+    let snapshot = auto_backup::take_snapshot(&conn, &backup_folder(app_handle, &config), db::passphrase(app_handle).as_deref())?;
+    println!("💾 Backup written to {:?}", snapshot.path);
+    Ok(snapshot)
+}
+
+fn snapshot_now(app_handle: &AppHandle) -> performance_tracker_core::Result<SnapshotInfo> {
+    let snapshot = snapshot_only(app_handle)?;
+    let conn = db::init_db(app_handle)?;
+    let config = auto_backup::load_config(&conn)?;
+    let removed = auto_backup::rotate(&backup_folder(app_handle, &config), &config)?;
+    println!("🧹 {} old backups removed", removed.len());
+    Ok(snapshot)
+}
+
+fn snapshot_if_due(app_handle: &AppHandle) -> performance_tracker_core::Result<()> {
+    let conn = db::init_db(app_handle)?;
+    let config = auto_backup::load_config(&conn)?;
+    if auto_backup::is_due(&backup_folder(app_handle, &config), &config)? {
+        snapshot_now(app_handle)?;
+    }
+    Ok(())
+}
+
+/// Starts the background task that takes a snapshot whenever the newest
+/// one is older than the configured interval.
+pub fn start(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        if let Err(e) = snapshot_if_due(&app_handle) {
+            println!("❌ Automatic backup failed: {}", e);
+        }
+        std::thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+/// Takes a last snapshot when the app exits.
+pub fn on_exit(app_handle: &AppHandle) {
+    if let Err(e) = snapshot_now(app_handle) {
+        println!("❌ Backup on exit failed: {}", e);
+    }
+}
+
+#[tauri::command]
+pub fn get_auto_backup_config(app_handle: AppHandle) -> Result<AutoBackupConfig, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    auto_backup::load_config(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_auto_backup_config(app_handle: AppHandle, config: AutoBackupConfig) -> Result<(), String> {
+    println!("⚙️ set_auto_backup_config command called: {:?}", config);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    auto_backup::save_config(&conn, &config).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_auto_backups(app_handle: AppHandle) -> Result<Vec<SnapshotInfo>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    let config = auto_backup::load_config(&conn).map_err(|e| e.to_string())?;
+    auto_backup::list(&backup_folder(&app_handle, &config)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn create_auto_backup(app_handle: AppHandle) -> Result<SnapshotInfo, String> {
+    println!("💾 create_auto_backup command called");
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    snapshot_now(&app_handle).map_err(|e| e.to_string())
+}
+
+/// Restores the snapshot `file_name` from the backup folder. The current
+/// state is snapshotted first, so the restore itself can be undone. That
+/// snapshot is not rotated, which could delete the one being restored.
+#[tauri::command]
+pub fn restore_auto_backup(app_handle: AppHandle, journal: State<'_, Journal>, file_name: String) -> Result<(), String> {
+    println!("♻️ restore_auto_backup command called: {}", file_name);
+    let mut conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    let config = auto_backup::load_config(&conn).map_err(|e| e.to_string())?;
+    let path = backup_folder(&app_handle, &config).join(&file_name);
+    let source = auto_backup::open(&path, db::passphrase(&app_handle).as_deref()).map_err(|e| e.to_string())?;
+    let before = snapshot_only(&app_handle).map_err(|e| e.to_string())?;
+    auto_backup::restore_from(&mut conn, &source).map_err(|e| e.to_string())?;
+    journal.clear();
+    println!("✅ Restored {} (previous state saved as {})", file_name, before.file_name);
+    Ok(())
+}
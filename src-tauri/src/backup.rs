@@ -0,0 +1,41 @@
+use performance_tracker_core::backup::{self, Backup, ConflictMode, RestoreSummary};
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::user::Permission;
+use tauri::{AppHandle, State};
+
+use crate::{db, user};
+
+/// Writes a JSON backup of the whole practice to `path`. The file is not
+/// encrypted; user accounts are only in it with `with_users`.
+#[tauri::command]
+pub fn export_backup(app_handle: AppHandle, path: String, with_users: Option<bool>) -> Result<(), String> {
+    println!("💾 export_backup command called: {} (with users: {:?})", path, with_users);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    let backup = backup::create(&conn, with_users.unwrap_or(false)).map_err(|e| e.to_string())?;
+    let json = serde_json::to_vec_pretty(&backup).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    println!("✅ Backup written to {}", path);
+    Ok(())
+}
+
+/// Restores a backup written by [`export_backup`]. `mode` decides what
+/// happens to rows that already exist: "replace", "merge" or "abort".
+#[tauri::command]
+pub fn import_backup(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    path: String,
+    mode: ConflictMode,
+) -> Result<RestoreSummary, String> {
+    println!("♻️ import_backup command called: {} ({:?})", path, mode);
+    let json = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let backup: Backup = serde_json::from_slice(&json).map_err(|e| format!("Not a valid backup file: {}", e))?;
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    let summary = backup::restore(&conn, &backup, mode).map_err(|e| e.to_string())?;
+    // Undo entries may point at rows the restore replaced.
+    journal.clear();
+    println!("✅ Backup from {} restored", backup.created_at);
+    Ok(summary)
+}
@@ -0,0 +1,43 @@
+use performance_tracker_core::comparison::{self, Period, Preset};
+use performance_tracker_core::user::Permission;
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::{db, user};
+
+/// Compares two periods of one employee or, without `employee_id`, of the
+/// practice. Either both periods are given or a preset picks them up to
+/// `reference_date`, which defaults to today.
+#[tauri::command]
+pub fn compare_stats(
+    app_handle: AppHandle,
+    employee_id: Option<i32>,
+    current: Option<Period>,
+    previous: Option<Period>,
+    preset: Option<Preset>,
+    reference_date: Option<String>,
+) -> Result<Value, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    match employee_id {
+        Some(employee_id) => user.ensure_employee(employee_id),
+        None => user.ensure(Permission::ViewAllEmployees),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let result = match (current, previous, preset) {
+        (Some(current), Some(previous), None) => comparison::compare(&conn, employee_id, &current, &previous),
+        (None, None, Some(preset)) => {
+            let reference = match reference_date {
+                Some(date) => date,
+                None => conn
+                    .query_row("SELECT date('now', 'localtime')", [], |row| row.get(0))
+                    .map_err(|e| e.to_string())?,
+            };
+            comparison::compare_preset(&conn, employee_id, preset, &reference)
+        }
+        _ => return Err("Either both periods or a preset must be given".to_string()),
+    };
+    let comparison = result.map_err(|e| e.to_string())?;
+    Ok(user.comparison_view(&comparison))
+}
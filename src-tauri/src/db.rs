@@ -1,9 +1,30 @@
-use rusqlite::{Connection, Result};
-use rusqlite::params;
+use performance_tracker_core::{audit, db, encryption, Error, Result};
+use rusqlite::Connection;
 use std::path::PathBuf;
+use std::sync::Mutex;
 use tauri::AppHandle;
 use tauri::Manager;
 
+use crate::user::Session;
+
+/// Passphrase of the encrypted database, entered once at startup.
+#[derive(Default)]
+pub struct DatabaseKey(Mutex<Option<String>>);
+
+impl DatabaseKey {
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, passphrase: Option<String>) {
+        *self.0.lock().unwrap() = passphrase;
+    }
+}
+
+pub fn passphrase(app_handle: &AppHandle) -> Option<String> {
+    app_handle.state::<DatabaseKey>().get()
+}
+
 pub fn get_db_path(app_handle: &AppHandle) -> PathBuf {
     // Use the official Tauri 2 data directory API
     let app_data_dir = app_handle
@@ -22,62 +43,16 @@ pub fn get_db_path(app_handle: &AppHandle) -> PathBuf {
 
 pub fn init_db(app_handle: &AppHandle) -> Result<Connection> {
     let path = get_db_path(app_handle);
-    let conn = Connection::open(&path)?;
+    let conn = match passphrase(app_handle) {
+        Some(passphrase) => db::open_encrypted(&path, &passphrase)?,
+        None if encryption::is_encrypted(&path)? => {
+            return Err(Error::Invalid("The database is locked. Enter the passphrase first.".to_string()))
+        }
+        None => db::open(&path)?,
+    };
+    if let Some(user) = app_handle.state::<Session>().get() {
+        audit::set_actor(&conn, &user.username)?;
+    }
     println!("🔌 Database connection opened at: {:?}", path);
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS employees (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            join_date TEXT NOT NULL,
-            monthly_rate REAL NOT NULL,
-            avg_hours REAL DEFAULT 8.0
-        )",
-        [],
-    )?;
-    println!("✅ Employees table ensured");
-    
-
-    // ---------------- PERFORMANCE ----------------
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS performance (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            employee_id INTEGER NOT NULL,
-            date TEXT NOT NULL,
-            hours_worked REAL,
-            status TEXT,
-            income REAL,
-            kg_num INTEGER,
-            mt_num INTEGER,
-            mld_num INTEGER,
-            mdl_45_num INTEGER,
-            mdl_60_num INTEGER,
-            ma_num INTEGER,
-            fango_num INTEGER,
-            ultraschal_num INTEGER,
-            hb_num INTEGER,
-            FOREIGN KEY(employee_id) REFERENCES employees(id)
-        )",
-        [],
-    )?;
-    println!("✅ Performance table ensured");
-    let _ = conn.execute(
-        "ALTER TABLE performance RENAME COLUMN mdl_60_num TO mld_60_num",
-        [],
-    );
-
-    // ---------------- THERAPY ----------------
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS therapy (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            therapy_name TEXT UNIQUE NOT NULL,
-            cost REAL,
-            income REAL
-        )",
-        [],
-    )?;
-    println!("✅ Therapy table ensured");
-
     Ok(conn)
 }
@@ -0,0 +1,70 @@
+use performance_tracker_core::user::Permission;
+use performance_tracker_core::{db as core_db, encryption};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::db::{self, DatabaseKey};
+use crate::user;
+
+#[derive(Debug, Serialize)]
+pub struct EncryptionStatus {
+    /// Whether this build was compiled with SQLCipher.
+    pub supported: bool,
+    pub encrypted: bool,
+    /// Whether the passphrase was entered in this session.
+    pub unlocked: bool,
+}
+
+/// Asked by the frontend at startup to decide whether to prompt for the passphrase.
+#[tauri::command]
+pub fn get_encryption_status(app_handle: AppHandle, key: State<'_, DatabaseKey>) -> Result<EncryptionStatus, String> {
+    let encrypted = encryption::is_encrypted(&db::get_db_path(&app_handle)).map_err(|e| e.to_string())?;
+    Ok(EncryptionStatus {
+        supported: encryption::SUPPORTED,
+        encrypted,
+        unlocked: !encrypted || key.get().is_some(),
+    })
+}
+
+/// Checks `passphrase` against the encrypted database and keeps it for
+/// the rest of the session.
+#[tauri::command]
+pub fn unlock_database(app_handle: AppHandle, key: State<'_, DatabaseKey>, passphrase: String) -> Result<(), String> {
+    println!("🔑 unlock_database command called");
+    core_db::open_encrypted(&db::get_db_path(&app_handle), &passphrase).map_err(|e| e.to_string())?;
+    key.set(Some(passphrase));
+    println!("✅ Database unlocked");
+    Ok(())
+}
+
+/// Encrypts the existing plain database in place with `passphrase`.
+#[tauri::command]
+pub fn encrypt_database(app_handle: AppHandle, key: State<'_, DatabaseKey>, passphrase: String) -> Result<(), String> {
+    println!("🔒 encrypt_database command called");
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    drop(conn);
+    let path = db::get_db_path(&app_handle);
+    encryption::encrypt_in_place(&path, &passphrase).map_err(|e| e.to_string())?;
+    key.set(Some(passphrase));
+    println!("✅ Database encrypted at {:?}", path);
+    Ok(())
+}
+
+/// Re-encrypts the database with `new_passphrase`. Automatic backups taken
+/// before keep the passphrase they were written with.
+#[tauri::command]
+pub fn change_database_passphrase(
+    app_handle: AppHandle,
+    key: State<'_, DatabaseKey>,
+    current_passphrase: String,
+    new_passphrase: String,
+) -> Result<(), String> {
+    println!("🔑 change_database_passphrase command called");
+    let conn = core_db::open_encrypted(&db::get_db_path(&app_handle), &current_passphrase).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    encryption::change_passphrase(&conn, &new_passphrase).map_err(|e| e.to_string())?;
+    key.set(Some(new_passphrase));
+    println!("✅ Database passphrase changed");
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use performance_tracker_core::export::csv::{self, CsvOptions};
+use performance_tracker_core::export::{pdf, xlsx};
+use performance_tracker_core::stats;
+use performance_tracker_core::user::Permission;
+use tauri::AppHandle;
+
+use crate::{db, user};
+
+/// Writes raw daily performance records to `path` as CSV. Without
+/// `employee_id` all employees are exported; `from` and `to` are inclusive
+/// `YYYY-MM-DD` dates.
+#[tauri::command]
+pub fn export_performance_csv(
+    app_handle: AppHandle,
+    path: String,
+    employee_id: Option<i32>,
+    from: Option<String>,
+    to: Option<String>,
+    options: Option<CsvOptions>,
+) -> Result<(), String> {
+    println!("📤 export_performance_csv command called: {:?} {:?} - {:?} -> {}", employee_id, from, to, path);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    match employee_id {
+        Some(id) => user.ensure_employee(id),
+        None => user.ensure(Permission::ViewAllEmployees),
+    }
+    .map_err(|e| e.to_string())?;
+    let content = csv::performance_csv(
+        &conn,
+        employee_id,
+        from.as_deref(),
+        to.as_deref(),
+        &options.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    println!("✅ Performance exported to {}", path);
+    Ok(())
+}
+
+/// Writes the monthly stats of one employee, or of the whole practice
+/// without `employee_id`, to `path` as CSV.
+#[tauri::command]
+pub fn export_monthly_stats_csv(
+    app_handle: AppHandle,
+    path: String,
+    employee_id: Option<i32>,
+    start_month: Option<String>,
+    end_month: Option<String>,
+    options: Option<CsvOptions>,
+) -> Result<(), String> {
+    println!("📤 export_monthly_stats_csv command called: {:?} {:?} - {:?} -> {}", employee_id, start_month, end_month, path);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ViewSalaries)?;
+    let monthly = match employee_id {
+        Some(id) => stats::monthly_stats(&conn, id, start_month.as_deref(), end_month.as_deref()),
+        None => stats::practice_monthly_stats(&conn, start_month.as_deref(), end_month.as_deref()),
+    }
+    .map_err(|e| e.to_string())?;
+    std::fs::write(&path, csv::monthly_stats_csv(&monthly, &options.unwrap_or_default()))
+        .map_err(|e| e.to_string())?;
+    println!("✅ Monthly stats exported to {}", path);
+    Ok(())
+}
+
+/// Writes an Excel workbook with a practice summary and one sheet of
+/// monthly stats per employee to `path`.
+#[tauri::command]
+pub fn export_monthly_stats_xlsx(
+    app_handle: AppHandle,
+    path: String,
+    start_month: Option<String>,
+    end_month: Option<String>,
+) -> Result<(), String> {
+    println!("📤 export_monthly_stats_xlsx command called: {:?} - {:?} -> {}", start_month, end_month, path);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ViewSalaries)?;
+    let workbook = xlsx::monthly_stats_xlsx(&conn, start_month.as_deref(), end_month.as_deref())
+        .map_err(|e| e.to_string())?;
+    std::fs::write(&path, workbook).map_err(|e| e.to_string())?;
+    println!("✅ Workbook exported to {}", path);
+    Ok(())
+}
+
+/// Writes a PDF report of an employee's monthly numbers, with charts, to
+/// `path`.
+#[tauri::command]
+pub fn export_employee_report_pdf(
+    app_handle: AppHandle,
+    path: String,
+    employee_id: i32,
+    start_month: Option<String>,
+    end_month: Option<String>,
+) -> Result<(), String> {
+    println!("📤 export_employee_report_pdf command called: {} {:?} - {:?} -> {}", employee_id, start_month, end_month, path);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ViewSalaries)?;
+    let report = pdf::employee_report_pdf(&conn, employee_id, start_month.as_deref(), end_month.as_deref())
+        .map_err(|e| e.to_string())?;
+    std::fs::write(&path, report).map_err(|e| e.to_string())?;
+    println!("✅ Report saved to {}", path);
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use performance_tracker_core::gdpr::{self, ErasureSummary, RetentionConfig};
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::user::Permission;
+use tauri::{AppHandle, State};
+
+use crate::{db, user};
+
+/// Writes everything stored about one employee to `path` as JSON, for a
+/// request for access under the GDPR.
+#[tauri::command]
+pub fn export_employee_data(app_handle: AppHandle, employee_id: i32, path: String) -> Result<(), String> {
+    println!("📤 export_employee_data command called: employee {} to {}", employee_id, path);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    let archive = gdpr::export(&conn, employee_id).map_err(|e| e.to_string())?;
+    let json = serde_json::to_vec_pretty(&archive).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    println!("✅ Data of employee {} written to {}", employee_id, path);
+    Ok(())
+}
+
+/// Anonymizes an employee for a request for erasure. Their figures stay
+/// in the practice totals.
+#[tauri::command]
+pub fn erase_employee(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    employee_id: i32,
+) -> Result<ErasureSummary, String> {
+    println!("🧹 erase_employee command called: employee {}", employee_id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    let config = gdpr::load_config(&conn).map_err(|e| e.to_string())?;
+    let summary = gdpr::erase(&conn, employee_id, &config).map_err(|e| e.to_string())?;
+    // Undoing an earlier change would bring the erased name back.
+    journal.clear();
+    println!("✅ Employee {} erased", employee_id);
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn get_retention_config(app_handle: AppHandle) -> Result<RetentionConfig, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    gdpr::load_config(&conn).map_err(|e| e.to_string())
+}
+
+/// Saves the retention periods and deletes what is now past them.
+#[tauri::command]
+pub fn set_retention_config(app_handle: AppHandle, config: RetentionConfig) -> Result<usize, String> {
+    println!("⚙️ set_retention_config command called: {:?}", config);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    gdpr::save_config(&conn, &config).map_err(|e| e.to_string())?;
+    gdpr::purge_expired(&conn, &config).map_err(|e| e.to_string())
+}
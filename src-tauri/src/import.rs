@@ -0,0 +1,66 @@
+use performance_tracker_core::import::csv::{self, ImportPreview, MappingProfile};
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::user::Permission;
+use tauri::{AppHandle, State};
+
+use crate::{db, user};
+
+fn read_file(path: &str) -> Result<String, String> {
+    std::fs::read(path).map(|bytes| csv::decode(&bytes)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_import_profiles(app_handle: AppHandle) -> Result<Vec<MappingProfile>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    csv::profiles(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_import_profile(app_handle: AppHandle, profile: MappingProfile) -> Result<(), String> {
+    println!("💾 save_import_profile command called: {}", profile.name);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    csv::save_profile(&conn, &profile).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_import_profile(app_handle: AppHandle, name: String) -> Result<(), String> {
+    println!("🗑️ delete_import_profile command called: {}", name);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    csv::delete_profile(&conn, &name).map_err(|e| e.to_string())
+}
+
+/// Dry run of [`import_performance_csv`]: the days that would be written
+/// and the errors of every row, without changing anything.
+#[tauri::command]
+pub fn preview_performance_import(app_handle: AppHandle, path: String, profile_name: String) -> Result<ImportPreview, String> {
+    println!("👀 preview_performance_import command called: {} ({})", path, profile_name);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    let profile = csv::profile(&conn, &profile_name).map_err(|e| e.to_string())?;
+    csv::preview(&conn, &read_file(&path)?, &profile).map_err(|e| e.to_string())
+}
+
+/// Imports the file with a saved mapping profile and returns the number
+/// of performance days written. Nothing is written if any row has errors.
+#[tauri::command]
+pub fn import_performance_csv(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    path: String,
+    profile_name: String,
+) -> Result<usize, String> {
+    println!("📥 import_performance_csv command called: {} ({})", path, profile_name);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    let profile = csv::profile(&conn, &profile_name).map_err(|e| e.to_string())?;
+    let changes = csv::apply(&conn, &read_file(&path)?, &profile).map_err(|e| e.to_string())?;
+    let count = changes.len();
+    for change in changes {
+        journal.push(&user, change);
+    }
+    println!("✅ Imported {} performance days", count);
+    Ok(count)
+}
@@ -0,0 +1,30 @@
+use performance_tracker_core::journal::Journal;
+use serde_json::Value;
+use tauri::{AppHandle, State};
+
+use crate::{db, user};
+
+/// Reverts the most recent change the logged-in user may undo and returns
+/// it, or `None` if there is nothing left to undo. Everyone can undo their
+/// own changes; those of others need the right to edit performance.
+/// Salaries in the change are left out for users who may not see them.
+#[tauri::command]
+pub fn undo_last_change(app_handle: AppHandle, journal: State<'_, Journal>) -> Result<Option<Value>, String> {
+    println!("↩️ undo_last_change command called");
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    let change = journal.undo(&conn, &user).map_err(|e| e.to_string())?;
+    Ok(change.map(|c| user.change_view(&c)))
+}
+
+/// Re-applies the most recently undone change the logged-in user may redo
+/// and returns it, or `None` if there is nothing to redo. Salaries in the
+/// change are left out for users who may not see them.
+#[tauri::command]
+pub fn redo(app_handle: AppHandle, journal: State<'_, Journal>) -> Result<Option<Value>, String> {
+    println!("↪️ redo command called");
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    let change = journal.redo(&conn, &user).map_err(|e| e.to_string())?;
+    Ok(change.map(|c| user.change_view(&c)))
+}
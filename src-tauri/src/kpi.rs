@@ -0,0 +1,21 @@
+use performance_tracker_core::kpi;
+use performance_tracker_core::user::Permission;
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::{db, user};
+
+/// Revenue, cost and treatments per worked hour and utilization of every
+/// employee per month, with the practice for comparison. Cost figures are
+/// left out for users who may not see salaries.
+#[tauri::command]
+pub fn get_monthly_kpis(
+    app_handle: AppHandle,
+    start_month: Option<String>,
+    end_month: Option<String>,
+) -> Result<Value, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::ViewAllEmployees)?;
+    let report = kpi::monthly_kpis(&conn, start_month.as_deref(), end_month.as_deref()).map_err(|e| e.to_string())?;
+    Ok(user.kpi_view(&report))
+}
@@ -1,14 +1,136 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
-}
+// Every command is only used through `generate_handler!` in `run()`, so a
+// command that isn't registered there is dead code and fails the build.
+// This relies on `build.removeUnusedCommands` staying off in
+// tauri.conf.json: with it on, `#[tauri::command]` allows dead code on
+// every command and the check silently stops working.
+#![deny(dead_code)]
+
+mod audit;
+mod auto_backup;
+mod backup;
+mod comparison;
+mod db;
+mod employee;
+mod encryption;
+mod export;
+mod gdpr;
+mod import;
+mod journal;
+mod kpi;
+mod performance;
+mod period;
+mod personnel_cost;
+mod profitability;
+mod recycle;
+mod review;
+mod self_service;
+mod settings;
+mod therapy;
+mod user;
 
+use db::DatabaseKey;
+use performance_tracker_core::journal::Journal;
+use user::Session;
+
+// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .manage(Journal::default())
+        .manage(DatabaseKey::default())
+        .manage(Session::default())
+        .invoke_handler(tauri::generate_handler![
+            employee::get_employees,
+            employee::get_employee,
+            employee::add_employee,
+            employee::update_employee,
+            employee::delete_employee,
+            employee::get_employee_name,
+            employee::get_employee_avg_hours,
+            performance::get_all_performance,
+            performance::add_performance,
+            performance::update_performance,
+            performance::delete_performance,
+            performance::get_monthly_stats,
+            performance::get_stats,
+            performance::get_daily_utilization,
+            kpi::get_monthly_kpis,
+            comparison::compare_stats,
+            personnel_cost::get_cost_components,
+            personnel_cost::add_cost_component,
+            personnel_cost::update_cost_component,
+            personnel_cost::delete_cost_component,
+            therapy::get_all_therapies,
+            therapy::update_therapy,
+            therapy::add_therapy,
+            therapy::delete_therapy,
+            profitability::get_therapy_profitability,
+            period::close_month,
+            period::reopen_month,
+            period::get_closed_periods,
+            period::get_period_log,
+            audit::get_audit_log,
+            journal::undo_last_change,
+            journal::redo,
+            recycle::get_recycle_bin,
+            recycle::restore_deleted,
+            recycle::purge_recycle_bin,
+            export::export_performance_csv,
+            export::export_monthly_stats_csv,
+            export::export_monthly_stats_xlsx,
+            export::export_employee_report_pdf,
+            import::get_import_profiles,
+            import::save_import_profile,
+            import::delete_import_profile,
+            import::preview_performance_import,
+            import::import_performance_csv,
+            backup::export_backup,
+            backup::import_backup,
+            gdpr::export_employee_data,
+            gdpr::erase_employee,
+            gdpr::get_retention_config,
+            gdpr::set_retention_config,
+            settings::get_settings,
+            settings::set_setting,
+            auto_backup::get_auto_backup_config,
+            auto_backup::set_auto_backup_config,
+            auto_backup::list_auto_backups,
+            auto_backup::create_auto_backup,
+            auto_backup::restore_auto_backup,
+            encryption::get_encryption_status,
+            encryption::unlock_database,
+            encryption::encrypt_database,
+            encryption::change_database_passphrase,
+            user::get_session,
+            user::login,
+            user::logout,
+            user::get_users,
+            user::add_user,
+            user::set_user_role,
+            user::reset_user_password,
+            user::change_own_password,
+            user::delete_user,
+            self_service::get_own_performance,
+            self_service::get_own_monthly_stats,
+            self_service::add_own_performance,
+            self_service::update_own_performance,
+            self_service::delete_own_performance,
+            self_service::submit_own_performance,
+            review::get_pending_performance,
+            review::approve_performance,
+            review::reject_performance,
+            review::reopen_performance
+        ])
+        .setup(|app| {
+            auto_backup::start(app.handle().clone());
+            Ok(())
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                auto_backup::on_exit(app_handle);
+            }
+        });
 }
@@ -1,75 +1,24 @@
-use serde::{Serialize, Deserialize};
-use rusqlite::{params, Result};
-use tauri::AppHandle;
-use crate::db;
-use crate::employee;
-// use chrono::{NaiveDate, ParseError};
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Performance {
-    pub id: i32,
-    pub employee_id: i32,
-    pub date: String,
-    pub hours_worked: f64,
-    pub status: String,
-    pub income: f64,
-    pub kg_num: i32,
-    pub mt_num: i32,
-    pub mld_num: i32,
-    pub mld_45_num: i32,
-    pub mld_60_num: i32,
-    pub ma_num: i32,
-    pub fango_num: i32,
-    pub ultraschal_num: i32,
-    pub hb_num: i32,
-}
+use performance_tracker_core::granularity::Granularity;
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::performance::{self, Performance, PerformanceInput};
+use performance_tracker_core::stats;
+use performance_tracker_core::user::Permission;
+use serde_json::Value;
+use tauri::{AppHandle, State};
+use crate::{db, user};
 
 #[tauri::command]
 pub fn get_all_performance(app_handle: AppHandle, employee_id: i32) -> Result<Vec<Performance>, String> {
     let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
-    let mut stmt = conn.prepare(
-        "SELECT id, employee_id, date, hours_worked, status, income, kg_num, mt_num, mld_num, mld_45_num, mld_60_num, ma_num, fango_num, ultraschal_num, hb_num
-         FROM performance 
-         WHERE employee_id = ?1"
-    ).map_err(|e| e.to_string())?;
-    
-    let performances_iter = stmt.query_map(params![employee_id], |row| {
-        Ok(Performance {
-            id: row.get(0)?,
-            employee_id: row.get(1)?,
-            date: row.get(2)?,
-            hours_worked: row.get(3)?,
-            status: row.get(4)?,
-            income: row.get(5)?,
-            kg_num: row.get(6)?,
-            mt_num: row.get(7)?,
-            mld_num: row.get(8)?,
-            mld_45_num: row.get(9)?,
-            mld_60_num: row.get(10)?,
-            ma_num: row.get(11)?,
-            fango_num: row.get(12)?,
-            ultraschal_num: row.get(13)?,
-            hb_num: row.get(14)?,
-        })
-    }).map_err(|e| e.to_string())?;
-
-    let mut performances = Vec::new();
-    for perf in performances_iter {
-        performances.push(perf.map_err(|e| e.to_string())?);
-    }
-
-    Ok(performances)
+    user::acting_user(&app_handle, &conn)?.ensure_employee(employee_id).map_err(|e| e.to_string())?;
+    performance::list(&conn, employee_id).map_err(|e| e.to_string())
 }
 
-
-//fn normalize_date(date_str: &str) -> Result<String, ParseError> {
-//    let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")?;
-//    Ok(date.format("%Y-%m-%d").to_string())
-//}
-
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn add_performance(
     app_handle: AppHandle,
+    journal: State<'_, Journal>,
     employee_id: i32,
     date: String,
     hours_worked: f64,
@@ -78,29 +27,43 @@ pub fn add_performance(
     kg_num: i32,
     mt_num: i32,
     mld_num: i32,
+    mld45_num: i32,
+    mld60_num: i32,
+    ma_num: i32,
     fango_num: i32,
     ultraschal_num: i32,
     hb_num: i32,
 ) -> Result<(), String> {
     println!("✅ Function Called add_performance");
     let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
-    //let safe_date = normalize_date(&date).map_err(|e| e.to_string())?;
-    let safe_date = date; // Assume date is already in correct format
-    conn.execute(
-        "INSERT INTO performance (employee_id, date, hours_worked, status, income, kg_num, mt_num, mld_num, fango_num, ultraschal_num, hb_num)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-        params![employee_id, safe_date, hours_worked, status, income, kg_num, mt_num, mld_num, fango_num, ultraschal_num, hb_num],
-    )
-    .map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    let input = PerformanceInput {
+        hours_worked,
+        status,
+        income,
+        kg_num,
+        mt_num,
+        mld_num,
+        mld_45_num: mld45_num,
+        mld_60_num: mld60_num,
+        ma_num,
+        fango_num,
+        ultraschal_num,
+        hb_num,
+    };
+    let change = performance::add(&conn, employee_id, &date, &input).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
 
-    println!("✅ Inserted record for {} with status {}", safe_date, status);
+    println!("✅ Inserted record for {} with status {}", date, input.status);
     Ok(())
 }
 
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn update_performance(
     app_handle: AppHandle,
+    journal: State<'_, Journal>,
     id: i32,
     hours_worked: f64,
     status: String,
@@ -116,34 +79,36 @@ pub fn update_performance(
     hb_num: i32,
 ) -> Result<(), String> {
     let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
-    conn.execute(
-        "UPDATE performance 
-         SET hours_worked = ?1, status = ?2, income = ?3, kg_num = ?4, mt_num = ?5, mld_num = ?6, mld_45_num = ?7, mld_60_num = ?8, ma_num = ?9, fango_num = ?10, ultraschal_num = ?11, hb_num = ?12 
-         WHERE id = ?13",
-        params![hours_worked, status, income, kg_num, mt_num, mld_num, mld45_num, mld60_num, ma_num, fango_num, ultraschal_num, hb_num, id],
-    )
-    .map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    let input = PerformanceInput {
+        hours_worked,
+        status,
+        income,
+        kg_num,
+        mt_num,
+        mld_num,
+        mld_45_num: mld45_num,
+        mld_60_num: mld60_num,
+        ma_num,
+        fango_num,
+        ultraschal_num,
+        hb_num,
+    };
+    let change = performance::update(&conn, id, &input).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
     Ok(())
 }
-#[derive(Serialize)]
-pub struct MonthlyStats {
-    pub month: String,
-    pub total_hours: f64,
-    pub work_days: i32,
-    pub sick_days: i32,
-    pub vacation_days: i32,
-    pub cost: f64,            // employee monthly rate
-    pub generated_income: f64, // income generated from therapies
-    pub total_kg: i32,
-    pub total_mt: i32,
-    pub total_mld: i32,
-    pub total_mld_45: i32,
-    pub total_mld_60: i32,
-    pub total_ma: i32,
-    pub total_fango: i32,
-    pub total_ultraschal: i32,
-    pub total_hb: i32,
-    pub hours_needed: f64,
+
+#[tauri::command]
+pub fn delete_performance(app_handle: AppHandle, journal: State<'_, Journal>, id: i32) -> Result<(), String> {
+    println!("➖ delete_performance command called: ID {}", id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    let change = performance::delete(&conn, id).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
+
+    println!("✅ Performance record moved to recycle bin");
+    Ok(())
 }
 
 #[tauri::command]
@@ -152,135 +117,73 @@ pub fn get_monthly_stats(
     employee_id: i32,
     start_month: Option<String>,
     end_month: Option<String>,
-) -> Result<Vec<MonthlyStats>, String> {
+    approved_only: Option<bool>,
+) -> Result<Value, String> {
     let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    user.ensure_employee(employee_id).map_err(|e| e.to_string())?;
+    println!("✅ START MONTH {:?} END MONTH {:?}", start_month, end_month);
+    let monthly = stats::monthly_stats_filtered(
+        &conn,
+        employee_id,
+        start_month.as_deref(),
+        end_month.as_deref(),
+        approved_only.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(user.stats_view(&monthly))
+}
 
-    // Fetch therapy prices
-    let mut therapy_stmt = conn
-        .prepare("SELECT therapy_name, income FROM therapy")
-        .map_err(|e| e.to_string())?;
-    let mut therapy_prices = std::collections::HashMap::new();
-    let therapy_iter = therapy_stmt
-        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))
-        .map_err(|e| e.to_string())?;
-    for item in therapy_iter {
-        let (name, price) = item.map_err(|e| e.to_string())?;
-        therapy_prices.insert(name, price);
+/// Booked treatment time per day of one employee or, without `employee_id`,
+/// of everyone. With `overbooked_only` only days with more treatment time
+/// than hours worked are returned.
+#[tauri::command]
+pub fn get_daily_utilization(
+    app_handle: AppHandle,
+    employee_id: Option<i32>,
+    from: Option<String>,
+    to: Option<String>,
+    overbooked_only: Option<bool>,
+) -> Result<Vec<stats::DayUtilization>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    match employee_id {
+        Some(employee_id) => user.ensure_employee(employee_id),
+        None => user.ensure(Permission::ViewAllEmployees),
     }
-
-    // Fetch employee avg_hours
-    let avg_hours = employee::get_employee_avg_hours(app_handle, employee_id).unwrap_or("8.0".to_string());
-    println!("✅ fetched avg_hours {} ", avg_hours);
-
-
-    // Base monthly performance query
-    let base_query = "
-        SELECT substr(date, 1, 7) AS month,
-               SUM(hours_worked) AS total_hours,
-               COUNT(CASE WHEN status = 'Present' THEN 1 END) AS work_days,
-               COUNT(CASE WHEN status = 'Krank' THEN 1 END) AS sick_days,
-               COUNT(CASE WHEN status = 'Urlaub' THEN 1 END) AS vacation_days,
-               SUM(kg_num) AS total_kg,
-               SUM(mt_num) AS total_mt,
-               SUM(mld_num) AS total_mld,
-               SUM(mld_45_num) AS total_mld_45,
-               SUM(mld_60_num) AS total_mld_60,
-               SUM(ma_num) AS total_ma,
-               SUM(fango_num) AS total_fango,
-               SUM(ultraschal_num) AS total_ultraschal,
-               SUM(hb_num) AS total_hb
-        FROM performance
-        WHERE employee_id = ?1
-    ";
-
-    let (query, with_range) = if start_month.is_some() && end_month.is_some() {
-        (
-            format!(
-                "{} AND substr(date, 1, 7) BETWEEN ?2 AND ?3 GROUP BY month ORDER BY month ASC",
-                base_query
-            ),
-            true,
-        )
+    .map_err(|e| e.to_string())?;
+    let days = if overbooked_only.unwrap_or(false) {
+        stats::overbooked_days(&conn, employee_id, from.as_deref(), to.as_deref())
     } else {
-        (
-            format!("{} GROUP BY month ORDER BY month ASC", base_query),
-            false,
-        )
-    };
-
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
-
-    // Get employee monthly rate (cost)
-    let emp_rate: f64 = match conn.query_row(
-    "SELECT monthly_rate FROM employees WHERE id = ?1",
-    params![employee_id],
-    |row| row.get(0),
-   ) {
-    Ok(rate) => rate,
-    Err(rusqlite::Error::QueryReturnedNoRows) => 0.0, // employee not found
-    Err(e) => return Err(e.to_string()),              // other SQL errors
-    };
-
-    let map_row = |row: &rusqlite::Row| -> Result<MonthlyStats, rusqlite::Error> {
-        let month: String = row.get(0)?;
-        let work_days = row.get(2)?;
-        let total_kg: i32 = row.get(5)?;
-        let total_mt: i32 = row.get(6)?;
-        let total_mld: i32 = row.get(7)?;
-        let total_mld_45: i32 = row.get(8)?;
-        let total_mld_60: i32 = row.get(9)?;
-        let total_ma: i32 = row.get(10)?;
-        let total_fango: i32 = row.get(11)?;
-        let total_ultraschal: i32 = row.get(12)?;
-        let total_hb: i32 = row.get(13)?;
-
-        let income = (total_kg as f64) * *therapy_prices.get("kg").unwrap_or(&0.0)
-            + (total_mt as f64) * *therapy_prices.get("mt").unwrap_or(&0.0)
-            + (total_mld as f64) * *therapy_prices.get("mld-30").unwrap_or(&0.0)
-            + (total_mld_45 as f64) * *therapy_prices.get("mld-45").unwrap_or(&0.0)
-            + (total_mld_60 as f64) * *therapy_prices.get("mld-60").unwrap_or(&0.0)
-            + (total_ma as f64) * *therapy_prices.get("ma").unwrap_or(&0.0)
-            + (total_fango as f64) * *therapy_prices.get("fango").unwrap_or(&0.0)
-            + (total_ultraschal as f64) * *therapy_prices.get("ultraschal").unwrap_or(&0.0)
-            + (total_hb as f64) * *therapy_prices.get("hb").unwrap_or(&0.0);
-        // let hours_needed = (emp_rate / 160.0 * avg_hours.parse::<f64>().unwrap_or(8.0)) as i32;
-        let hours_needed = avg_hours.parse::<f64>().unwrap() * work_days as f64;
-
-        Ok(MonthlyStats {
-            month,
-            total_hours: row.get(1)?,
-            work_days,
-            sick_days: row.get(3)?,
-            vacation_days: row.get(4)?,
-            cost: emp_rate,
-            generated_income: income,
-            total_kg,
-            total_mt,
-            total_mld,
-            total_mld_45,
-            total_mld_60,
-            total_ma,
-            total_fango,
-            total_ultraschal,
-            total_hb,
-            hours_needed,
-        })
+        stats::daily_utilization(&conn, employee_id, from.as_deref(), to.as_deref())
     };
+    days.map_err(|e| e.to_string())
+}
 
-    let stats_iter = if with_range {
-        let start = start_month.unwrap();
-        let end = end_month.unwrap();
-        println!("✅ START MONTH {} END MONTH {}", start, end);
-        stmt.query_map(params![employee_id, start, end], map_row)
-    } else {
-        stmt.query_map(params![employee_id], map_row)
+/// Stats of one employee or, without `employee_id`, of the practice per
+/// day, week, month, quarter or year. `from` and `to` are inclusive dates.
+#[tauri::command]
+pub fn get_stats(
+    app_handle: AppHandle,
+    employee_id: Option<i32>,
+    granularity: Granularity,
+    from: Option<String>,
+    to: Option<String>,
+    approved_only: Option<bool>,
+) -> Result<Value, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    let (from, to, approved_only) = (from.as_deref(), to.as_deref(), approved_only.unwrap_or(false));
+    let buckets = match employee_id {
+        Some(employee_id) => {
+            user.ensure_employee(employee_id).map_err(|e| e.to_string())?;
+            stats::stats_by(&conn, employee_id, granularity, from, to, approved_only)
+        }
+        None => {
+            user.ensure(Permission::ViewAllEmployees).map_err(|e| e.to_string())?;
+            stats::practice_stats_by(&conn, granularity, from, to, approved_only)
+        }
     }
     .map_err(|e| e.to_string())?;
-
-    let mut stats = Vec::new();
-    for s in stats_iter {
-        stats.push(s.map_err(|e| e.to_string())?);
-    }
-
-    Ok(stats)
-}
\ No newline at end of file
+    Ok(user.stats_view(&buckets))
+}
@@ -0,0 +1,39 @@
+use performance_tracker_core::period::{self, ClosedPeriod, PeriodLogEntry};
+use performance_tracker_core::user::Permission;
+use tauri::AppHandle;
+
+use crate::{db, user};
+
+#[tauri::command]
+pub fn close_month(app_handle: AppHandle, month: String) -> Result<(), String> {
+    println!("🔒 close_month command called: {}", month);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManagePeriods)?;
+    period::close_month(&conn, &month).map_err(|e| e.to_string())?;
+    println!("✅ Month {} closed", month);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn reopen_month(app_handle: AppHandle, month: String, reason: Option<String>) -> Result<(), String> {
+    println!("🔓 reopen_month command called: {} ({:?})", month, reason);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManagePeriods)?;
+    period::reopen_month(&conn, &month, reason.as_deref()).map_err(|e| e.to_string())?;
+    println!("✅ Month {} reopened", month);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_closed_periods(app_handle: AppHandle) -> Result<Vec<ClosedPeriod>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManagePeriods)?;
+    period::closed_periods(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_period_log(app_handle: AppHandle) -> Result<Vec<PeriodLogEntry>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManagePeriods)?;
+    period::period_log(&conn).map_err(|e| e.to_string())
+}
@@ -0,0 +1,56 @@
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::personnel_cost::{self, CostComponent, CostComponentInput};
+use performance_tracker_core::user::Permission;
+use tauri::{AppHandle, State};
+
+use crate::{db, user};
+
+/// Employer cost components of one employee. They reveal the salary, so
+/// only users who may see salaries get them.
+#[tauri::command]
+pub fn get_cost_components(app_handle: AppHandle, employee_id: i32) -> Result<Vec<CostComponent>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ViewSalaries)?;
+    personnel_cost::list(&conn, employee_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn add_cost_component(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    employee_id: i32,
+    component: CostComponentInput,
+) -> Result<(), String> {
+    println!("➕ add_cost_component command called: employee {} - {}", employee_id, component.name);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::ViewSalaries)?;
+    let change = personnel_cost::add(&conn, employee_id, &component).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
+    println!("✅ Cost component added");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_cost_component(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    id: i32,
+    component: CostComponentInput,
+) -> Result<(), String> {
+    println!("✏️ update_cost_component command called: ID {}", id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::ViewSalaries)?;
+    let change = personnel_cost::update(&conn, id, &component).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_cost_component(app_handle: AppHandle, journal: State<'_, Journal>, id: i32) -> Result<(), String> {
+    println!("➖ delete_cost_component command called: ID {}", id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::ViewSalaries)?;
+    let change = personnel_cost::delete(&conn, id).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
+    Ok(())
+}
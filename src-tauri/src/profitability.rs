@@ -0,0 +1,18 @@
+use performance_tracker_core::profitability::{self, Profitability};
+use performance_tracker_core::user::Permission;
+use tauri::AppHandle;
+
+use crate::{db, user};
+
+/// Count, revenue, material cost and contribution margin per therapy, for
+/// the practice and each employee. Without months, everything is included.
+#[tauri::command]
+pub fn get_therapy_profitability(
+    app_handle: AppHandle,
+    start_month: Option<String>,
+    end_month: Option<String>,
+) -> Result<Profitability, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ViewAllEmployees)?;
+    profitability::analyze(&conn, start_month.as_deref(), end_month.as_deref()).map_err(|e| e.to_string())
+}
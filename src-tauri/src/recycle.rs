@@ -0,0 +1,43 @@
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::recycle::{self, RecycleBinItem, DEFAULT_RETENTION_DAYS};
+use performance_tracker_core::user::Permission;
+use tauri::{AppHandle, State};
+
+use crate::{db, user};
+
+#[tauri::command]
+pub fn get_recycle_bin(app_handle: AppHandle) -> Result<Vec<RecycleBinItem>, String> {
+    println!("🗑️ get_recycle_bin command called");
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    recycle::list(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn restore_deleted(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    entity: String,
+    id: i64,
+) -> Result<(), String> {
+    println!("♻️ restore_deleted command called: {} #{}", entity, id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::ManageData)?;
+    let change = recycle::restore(&conn, &entity, id).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
+    println!("✅ Restored {} #{}", entity, id);
+    Ok(())
+}
+
+/// Permanently removes items deleted more than `retention_days` ago
+/// (30 by default) and returns how many rows were removed.
+#[tauri::command]
+pub fn purge_recycle_bin(app_handle: AppHandle, retention_days: Option<i64>) -> Result<usize, String> {
+    let retention_days = retention_days.unwrap_or(DEFAULT_RETENTION_DAYS);
+    println!("🔥 purge_recycle_bin command called: older than {} days", retention_days);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    let purged = recycle::purge(&conn, retention_days).map_err(|e| e.to_string())?;
+    println!("✅ Purged {} items from the recycle bin", purged);
+    Ok(purged)
+}
@@ -0,0 +1,57 @@
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::performance::Performance;
+use performance_tracker_core::review;
+use performance_tracker_core::user::Permission;
+use tauri::{AppHandle, State};
+
+use crate::{db, user};
+
+/// Days therapists submitted, of one employee or of everyone.
+#[tauri::command]
+pub fn get_pending_performance(app_handle: AppHandle, employee_id: Option<i32>) -> Result<Vec<Performance>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    review::pending(&conn, employee_id).map_err(|e| e.to_string())
+}
+
+/// Approves the submitted days `ids`; nothing is approved if one of them
+/// isn't waiting for approval.
+#[tauri::command]
+pub fn approve_performance(app_handle: AppHandle, journal: State<'_, Journal>, ids: Vec<i32>) -> Result<(), String> {
+    println!("✅ approve_performance command called: {:?}", ids);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    for change in review::approve(&conn, &ids).map_err(|e| e.to_string())? {
+        journal.push(&user, change);
+    }
+    Ok(())
+}
+
+/// Sends the submitted days `ids` back to the therapist with `comment`.
+#[tauri::command]
+pub fn reject_performance(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    ids: Vec<i32>,
+    comment: String,
+) -> Result<(), String> {
+    println!("↩️ reject_performance command called: {:?} ({})", ids, comment);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    for change in review::reject(&conn, &ids, &comment).map_err(|e| e.to_string())? {
+        journal.push(&user, change);
+    }
+    Ok(())
+}
+
+/// Hands the approved days `ids` back to the therapist as drafts.
+#[tauri::command]
+pub fn reopen_performance(app_handle: AppHandle, journal: State<'_, Journal>, ids: Vec<i32>) -> Result<(), String> {
+    println!("🔓 reopen_performance command called: {:?}", ids);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::EditPerformance)?;
+    for change in review::reopen(&conn, &ids).map_err(|e| e.to_string())? {
+        journal.push(&user, change);
+    }
+    Ok(())
+}
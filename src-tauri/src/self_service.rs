@@ -0,0 +1,95 @@
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::performance::{Performance, PerformanceInput};
+use performance_tracker_core::self_service::{self, OwnMonthlyStats};
+use tauri::{AppHandle, State};
+
+use crate::{db, user};
+
+/// Performance days of the logged-in employee.
+#[tauri::command]
+pub fn get_own_performance(app_handle: AppHandle) -> Result<Vec<Performance>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    self_service::own_performance(&conn, &user).map_err(|e| e.to_string())
+}
+
+/// Monthly summary of the logged-in employee, without cost.
+#[tauri::command]
+pub fn get_own_monthly_stats(
+    app_handle: AppHandle,
+    start_month: Option<String>,
+    end_month: Option<String>,
+) -> Result<Vec<OwnMonthlyStats>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    self_service::own_monthly_stats(&conn, &user, start_month.as_deref(), end_month.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Enters a day for the logged-in employee as a draft.
+#[tauri::command]
+pub fn add_own_performance(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    date: String,
+    performance: PerformanceInput,
+) -> Result<(), String> {
+    println!("📝 add_own_performance command called: {}", date);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    let change = self_service::enter_day(&conn, &user, &date, &performance).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
+    println!("✅ {} entered {}", user.username, date);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_own_performance(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    id: i32,
+    performance: PerformanceInput,
+) -> Result<(), String> {
+    println!("✏️ update_own_performance command called: ID {}", id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    let change = self_service::update_day(&conn, &user, id, &performance).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_own_performance(app_handle: AppHandle, journal: State<'_, Journal>, id: i32) -> Result<(), String> {
+    println!("➖ delete_own_performance command called: ID {}", id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    let change = self_service::delete_day(&conn, &user, id).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
+    Ok(())
+}
+
+/// Submits the logged-in employee's drafts and rejected days of the week
+/// containing `date`, or of `month` (`YYYY-MM`), and returns how many.
+#[tauri::command]
+pub fn submit_own_performance(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    date: Option<String>,
+    month: Option<String>,
+) -> Result<usize, String> {
+    println!("📨 submit_own_performance command called: week of {:?}, month {:?}", date, month);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::acting_user(&app_handle, &conn)?;
+    let changes = match (date, month) {
+        (Some(date), None) => self_service::submit_week(&conn, &user, &date),
+        (None, Some(month)) => self_service::submit_month(&conn, &user, &month),
+        _ => return Err("Submit either the week of a date or a month".to_string()),
+    }
+    .map_err(|e| e.to_string())?;
+    let count = changes.len();
+    for change in changes {
+        journal.push(&user, change);
+    }
+    println!("✅ {} submitted {} days", user.username, count);
+    Ok(count)
+}
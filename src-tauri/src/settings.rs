@@ -0,0 +1,21 @@
+use performance_tracker_core::settings;
+use performance_tracker_core::user::Permission;
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::{db, user};
+
+#[tauri::command]
+pub fn get_settings(app_handle: AppHandle) -> Result<BTreeMap<String, String>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    settings::all(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_setting(app_handle: AppHandle, key: String, value: String) -> Result<(), String> {
+    println!("⚙️ set_setting command called: {} = {}", key, value);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::require(&app_handle, &conn, Permission::ManageData)?;
+    settings::set(&conn, &key, &value).map_err(|e| e.to_string())
+}
@@ -1,76 +1,78 @@
-use serde::{Serialize, Deserialize};
-use rusqlite::{params, Result};
-use tauri::AppHandle;
-use crate::db;
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::therapy::{self, Therapy, TherapyInput};
+use performance_tracker_core::user::Permission;
+use tauri::{AppHandle, State};
+use crate::{db, user};
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Therapy {
-    pub id: i32,
-    pub therapy_name: String,
-    pub cost: f64,
-    pub income: f64,
-}
 #[tauri::command]
 pub fn get_all_therapies(app_handle: AppHandle) -> Result<Vec<Therapy>, String> {
     let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
-    let mut stmt = conn.prepare(
-        "SELECT id, therapy_name, cost, income 
-         FROM therapy"
-    ).map_err(|e| e.to_string())?;
-    
-    let therapies_iter = stmt.query_map(params![], |row| {
-        Ok(Therapy {
-            id: row.get(0)?,
-            therapy_name: row.get(1)?,
-            cost: row.get(2)?,
-            income: row.get(3)?,
-        })
-    }).map_err(|e| e.to_string())?;
-
-    let mut therapies = Vec::new();
-    for th in therapies_iter {
-        therapies.push(th.map_err(|e| e.to_string())?);
-    }
-
-    Ok(therapies)
+    user::acting_user(&app_handle, &conn)?;
+    therapy::list(&conn).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn update_therapy(
     app_handle: AppHandle,
+    journal: State<'_, Journal>,
     id: i32,
     therapy_name: String,
     cost: f64,
     income: f64,
+    duration_minutes: Option<i32>,
 ) -> Result<(), String> {
     let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
-    conn.execute(
-        "UPDATE therapy 
-         SET therapy_name = ?1, cost = ?2, income = ?3 
-         WHERE id = ?4",
-        params![therapy_name, cost, income, id],
-    )
-    .map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::EditTherapies)?;
+    // Frontends that don't know durations keep the stored one
+    let duration_minutes = match duration_minutes {
+        Some(minutes) => minutes,
+        None => therapy::list(&conn)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|t| t.id == id)
+            .map_or(0, |t| t.duration_minutes),
+    };
+    let input = TherapyInput { therapy_name, cost, income, duration_minutes };
+    let change = therapy::update(&conn, id, &input).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
     Ok(())
 }
 #[tauri::command]
-pub fn add_therapy(app_handle: AppHandle, therapy_name: String, cost: f64, income: f64) -> Result<(), String> {
+pub fn add_therapy(
+    app_handle: AppHandle,
+    journal: State<'_, Journal>,
+    therapy_name: String,
+    cost: f64,
+    income: f64,
+    duration_minutes: Option<i32>,
+) -> Result<(), String> {
     println!("➕ add_therapy command called: {} - {} - {}", therapy_name, cost, income);
     
     let conn = db::init_db(&app_handle).map_err(|e| {
         println!("❌ Database init error: {}", e);
         e.to_string()
     })?;
+    let user = user::require(&app_handle, &conn, Permission::EditTherapies)?;
     
-    conn.execute(
-        "INSERT INTO therapy (therapy_name, cost, income) VALUES (?1, ?2, ?3)",
-        params![therapy_name, cost, income],
-    )
-    .map_err(|e| {
+    let input = TherapyInput { therapy_name, cost, income, duration_minutes: duration_minutes.unwrap_or(0) };
+    let change = therapy::add(&conn, &input).map_err(|e| {
         println!("❌ Insert error: {}", e);
         e.to_string()
     })?;
+    journal.push(&user, change);
     
     println!("✅ Therapy added successfully");
     Ok(())
-}
\ No newline at end of file
+}
+
+#[tauri::command]
+pub fn delete_therapy(app_handle: AppHandle, journal: State<'_, Journal>, id: i32) -> Result<(), String> {
+    println!("➖ delete_therapy command called: ID {}", id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::require(&app_handle, &conn, Permission::EditTherapies)?;
+    let change = therapy::delete(&conn, id).map_err(|e| e.to_string())?;
+    journal.push(&user, change);
+
+    println!("✅ Therapy moved to recycle bin");
+    Ok(())
+}
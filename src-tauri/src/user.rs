@@ -0,0 +1,142 @@
+use performance_tracker_core::journal::Journal;
+use performance_tracker_core::user::{self, Permission, Role, User, UserInput};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::db;
+
+/// The user logged in to this window.
+#[derive(Default)]
+pub struct Session(Mutex<Option<User>>);
+
+impl Session {
+    pub fn get(&self) -> Option<User> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn set(&self, user: Option<User>) {
+        *self.0.lock().unwrap() = user;
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    pub user: Option<User>,
+    /// No account exists yet; the frontend should ask for the owner account.
+    pub setup_required: bool,
+}
+
+/// The logged-in user. Until the first account exists everyone acts as
+/// the owner, so an existing installation keeps working until it is set up.
+pub fn acting_user(app_handle: &AppHandle, conn: &Connection) -> Result<User, String> {
+    if let Some(user) = app_handle.state::<Session>().get() {
+        return Ok(user);
+    }
+    if user::count(conn).map_err(|e| e.to_string())? == 0 {
+        return Ok(User::setup_owner());
+    }
+    Err("Please log in first".to_string())
+}
+
+/// The logged-in user if their role has `permission`.
+pub fn require(app_handle: &AppHandle, conn: &Connection, permission: Permission) -> Result<User, String> {
+    let user = acting_user(app_handle, conn)?;
+    user.ensure(permission).map_err(|e| e.to_string())?;
+    Ok(user)
+}
+
+#[tauri::command]
+pub fn get_session(app_handle: AppHandle, session: State<'_, Session>) -> Result<SessionInfo, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    Ok(SessionInfo {
+        user: session.get(),
+        setup_required: user::count(&conn).map_err(|e| e.to_string())? == 0,
+    })
+}
+
+/// Logs in and starts a fresh undo history for the new user.
+#[tauri::command]
+pub fn login(
+    app_handle: AppHandle,
+    session: State<'_, Session>,
+    journal: State<'_, Journal>,
+    username: String,
+    password: String,
+) -> Result<User, String> {
+    println!("🔑 login command called: {}", username);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    let user = user::authenticate(&conn, &username, &password).map_err(|e| {
+        println!("❌ Login failed for {}", username);
+        e.to_string()
+    })?;
+    session.set(Some(user.clone()));
+    journal.clear();
+    println!("✅ Logged in as {} ({})", user.username, user.role.as_str());
+    Ok(user)
+}
+
+#[tauri::command]
+pub fn logout(session: State<'_, Session>, journal: State<'_, Journal>) {
+    println!("🔒 logout command called");
+    session.set(None);
+    journal.clear();
+}
+
+#[tauri::command]
+pub fn get_users(app_handle: AppHandle) -> Result<Vec<User>, String> {
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    require(&app_handle, &conn, Permission::ManageUsers)?;
+    user::list(&conn).map_err(|e| e.to_string())
+}
+
+/// Creates an account. Before any account exists this creates the owner.
+#[tauri::command]
+pub fn add_user(app_handle: AppHandle, user: UserInput) -> Result<User, String> {
+    println!("➕ add_user command called: {} ({})", user.username, user.role.as_str());
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    require(&app_handle, &conn, Permission::ManageUsers)?;
+    let created = user::add(&conn, &user).map_err(|e| e.to_string())?;
+    println!("✅ User {} created", created.username);
+    Ok(created)
+}
+
+#[tauri::command]
+pub fn set_user_role(app_handle: AppHandle, id: i32, role: Role, employee_id: Option<i32>) -> Result<User, String> {
+    println!("✏️ set_user_role command called: ID {} -> {}", id, role.as_str());
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    require(&app_handle, &conn, Permission::ManageUsers)?;
+    user::set_role(&conn, id, role, employee_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn reset_user_password(app_handle: AppHandle, id: i32, password: String) -> Result<(), String> {
+    println!("🔑 reset_user_password command called: ID {}", id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    require(&app_handle, &conn, Permission::ManageUsers)?;
+    user::set_password(&conn, id, &password).map_err(|e| e.to_string())
+}
+
+/// Changes the password of the logged-in user, who has to confirm the current one.
+#[tauri::command]
+pub fn change_own_password(
+    app_handle: AppHandle,
+    session: State<'_, Session>,
+    current_password: String,
+    new_password: String,
+) -> Result<(), String> {
+    println!("🔑 change_own_password command called");
+    let current = session.get().ok_or("Please log in first")?;
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    user::authenticate(&conn, &current.username, &current_password).map_err(|e| e.to_string())?;
+    user::set_password(&conn, current.id, &new_password).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_user(app_handle: AppHandle, id: i32) -> Result<(), String> {
+    println!("➖ delete_user command called: ID {}", id);
+    let conn = db::init_db(&app_handle).map_err(|e| e.to_string())?;
+    require(&app_handle, &conn, Permission::ManageUsers)?;
+    user::delete(&conn, id).map_err(|e| e.to_string())
+}